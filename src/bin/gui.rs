@@ -0,0 +1,226 @@
+// `cargo build --features gui`でのみビルドされる薄いGUIフロントエンド。
+// カートリッジの検出結果やI/Oは全てライブラリ側(`gb_reader::board`/
+// `gb_reader::mbc`)に委譲し、ここでは画面の組み立てとボタン操作の
+// 中継だけを行う。ボード操作はブロッキングIOのため、描画スレッドを
+// 止めないよう別スレッドで実行し、進捗をチャネル経由で受け取る。
+
+use eframe::egui;
+use gb_reader::board::CubicStyleBoard;
+use gb_reader::mbc::{
+    new_mbc_reader, new_ram_reader, read_ram_to_vec, read_rom_to_vec, write_ram_banked,
+    NewMbcReaderOptions, NewRamReaderOptions, MBC1_MODE_ROM_BANKING,
+};
+use gb_reader::rom::RomHeader;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+enum Action {
+    DumpRom(String),
+    BackupSave(String),
+    RestoreSave(String),
+}
+
+enum Event {
+    Header(RomHeader),
+    Progress(usize, usize),
+    Done(Result<String, String>),
+}
+
+fn worker(action: Action, tx: Sender<Event>) {
+    let result = (|| -> anyhow::Result<String> {
+        let mut board = CubicStyleBoard::new()?;
+
+        match action {
+            Action::DumpRom(output) => {
+                let (mut reader, header) = new_mbc_reader(&mut board, NewMbcReaderOptions::default())?;
+                let _ = tx.send(Event::Header(header));
+
+                let data = read_rom_to_vec(reader.as_mut(), Some(header.rom_size), |done| {
+                    let _ = tx.send(Event::Progress(done, header.rom_size));
+                })?;
+
+                std::fs::write(&output, &data)?;
+
+                Ok(format!("ROMを{}へ書き出しました ({}バイト)", output, data.len()))
+            }
+            Action::BackupSave(output) => {
+                let (_, header) = new_mbc_reader(&mut board, NewMbcReaderOptions::default())?;
+                let _ = tx.send(Event::Header(header));
+
+                board.enable_ram()?;
+
+                let mut reader = new_ram_reader(
+                    &mut board,
+                    &header,
+                    NewRamReaderOptions {
+                        mbc1_mode: MBC1_MODE_ROM_BANKING,
+                        fill_byte: 0xFF,
+                        ..Default::default()
+                    },
+                )?;
+                let total = reader.size();
+
+                let data = read_ram_to_vec(reader.as_mut(), Some(total), |done| {
+                    let _ = tx.send(Event::Progress(done, total));
+                })?;
+
+                drop(reader);
+                board.disable_ram()?;
+                board.reset()?;
+
+                std::fs::write(&output, &data)?;
+
+                Ok(format!("セーブを{}へ書き出しました ({}バイト)", output, data.len()))
+            }
+            Action::RestoreSave(input) => {
+                let (_, header) = new_mbc_reader(&mut board, NewMbcReaderOptions::default())?;
+                let _ = tx.send(Event::Header(header));
+
+                let data = std::fs::read(&input)?;
+                let ram_size = header.ram_size_bytes();
+                let data = &data[..ram_size.min(data.len())];
+
+                board.enable_ram()?;
+
+                write_ram_banked(&mut board, data, &header, |done, total| {
+                    let _ = tx.send(Event::Progress(done, total));
+                })?;
+
+                board.disable_ram()?;
+                board.reset()?;
+
+                Ok(format!("{}からセーブを復元しました ({}バイト)", input, data.len()))
+            }
+        }
+    })();
+
+    let _ = tx.send(Event::Done(result.map_err(|e| e.to_string())));
+}
+
+struct GbReaderApp {
+    output_path: String,
+    header: Option<RomHeader>,
+    progress: Option<(usize, usize)>,
+    status: String,
+    busy: bool,
+    rx: Option<Receiver<Event>>,
+}
+
+impl Default for GbReaderApp {
+    fn default() -> Self {
+        Self {
+            output_path: "dump.gb".to_string(),
+            header: None,
+            progress: None,
+            status: "カートリッジを挿入し、操作を選んでください".to_string(),
+            busy: false,
+            rx: None,
+        }
+    }
+}
+
+impl GbReaderApp {
+    fn start(&mut self, action: Action) {
+        let (tx, rx) = channel();
+
+        self.busy = true;
+        self.progress = None;
+        self.rx = Some(rx);
+
+        thread::spawn(move || worker(action, tx));
+    }
+
+    fn poll(&mut self) {
+        let Some(rx) = &self.rx else { return };
+
+        let mut done = false;
+
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                Event::Header(header) => self.header = Some(header),
+                Event::Progress(progress, total) => self.progress = Some((progress, total)),
+                Event::Done(result) => {
+                    self.status = match result {
+                        Ok(message) => message,
+                        Err(error) => format!("エラー: {}", error),
+                    };
+                    self.busy = false;
+                    done = true;
+                }
+            }
+        }
+
+        if done {
+            self.rx = None;
+        }
+    }
+}
+
+impl eframe::App for GbReaderApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll();
+
+        if self.busy {
+            ctx.request_repaint();
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("gb-reader");
+
+            if let Some(header) = &self.header {
+                ui.label(format!(
+                    "タイトル: {}, MBC: {:?}, ROMサイズ: {}バイト",
+                    header.title_str(),
+                    header.mbc_type,
+                    header.rom_size
+                ));
+            } else {
+                ui.label("カートリッジ未検出");
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("ファイル:");
+                ui.text_edit_singleline(&mut self.output_path);
+            });
+
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.busy, |ui| {
+                    if ui.button("Dump ROM").clicked() {
+                        self.start(Action::DumpRom(self.output_path.clone()));
+                    }
+
+                    if ui.button("Backup Save").clicked() {
+                        self.start(Action::BackupSave(self.output_path.clone()));
+                    }
+
+                    if ui.button("Restore Save").clicked() {
+                        self.start(Action::RestoreSave(self.output_path.clone()));
+                    }
+                });
+            });
+
+            if let Some((done, total)) = self.progress {
+                let fraction = if total == 0 {
+                    1.0
+                } else {
+                    done as f32 / total as f32
+                };
+
+                ui.add(egui::ProgressBar::new(fraction).show_percentage());
+            }
+
+            ui.separator();
+            ui.label(&self.status);
+        });
+    }
+}
+
+fn main() -> eframe::Result<()> {
+    eframe::run_native(
+        "gb-reader",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Box::new(GbReaderApp::default())),
+    )
+}
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use rppal::gpio::{Gpio, OutputPin};
 use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -30,6 +33,11 @@ enum Pin {
 
 const DEV_ID: u8 = 0;
 
+// RTCやEEPROM搭載カートリッジの一部は、電源投入直後の数バイトが
+// 不安定になることが確認されている。最初のトランザクション前に
+// 少し待つことで安定させる。
+pub const DEFAULT_WARMUP_MS: u64 = 50;
+
 const MCP23X08_IODIR: u8 = 0x00;
 const MCP23X08_IOCON: u8 = 0x05;
 const MCP23X08_GPIO: u8 = 0x09;
@@ -50,6 +58,107 @@ enum DataDir {
     Output,
 }
 
+/// パススルー/レベルシフタ経由のアダプタ基板を挟んでカートリッジに
+/// 接続する場合、`init()`の後に追加のハンドシェイクが必要になることが
+/// ある。このボード自体は素のGPIO/MCP23X08バスで、アダプタごとの
+/// プロトコルは基板の実装依存のため、実機で確認できたハンドシェイクが
+/// 判明したアダプタのみをここに追加していく方針とする。現状は直結
+/// (`Direct`)のみをサポートし、追加のシーケンスは発行しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterKind {
+    Direct,
+}
+
+impl Default for AdapterKind {
+    fn default() -> Self {
+        AdapterKind::Direct
+    }
+}
+
+/// `--protocol-trace`で有効化されるトランザクションログの出力先。
+/// ファイルパスが指定されない場合は標準エラーに出す。
+enum TraceSink {
+    File(BufWriter<File>),
+    Stderr,
+}
+
+/// `set_addr`/`read_byte`/`write_byte`/`enable_ram`の呼び出しを1行1件の
+/// NDJSONとして記録する。新しいマッパーの実装や、不審なダンプの原因
+/// 切り分けに使うためのもので、デフォルトでは無効(`CubicStyleBoard`は
+/// `trace`が`None`の状態で生成される)。ボード自体は`Board`
+/// のようなトレイトを持たず`CubicStyleBoard`一つだけの具象型なので、
+/// デコレータ型を別途用意するのではなく、このボード自身に組み込む。
+struct ProtocolTracer {
+    sink: TraceSink,
+}
+
+impl ProtocolTracer {
+    fn to_file(path: &str) -> Result<Self> {
+        Ok(Self {
+            sink: TraceSink::File(BufWriter::new(File::create(path)?)),
+        })
+    }
+
+    fn to_stderr() -> Self {
+        Self {
+            sink: TraceSink::Stderr,
+        }
+    }
+
+    fn record(&mut self, line: &str) {
+        let result = match &mut self.sink {
+            TraceSink::File(w) => writeln!(w, "{}", line),
+            TraceSink::Stderr => writeln!(std::io::stderr(), "{}", line),
+        };
+
+        // トレースは診断用の副作用であり、書き込みに失敗したからといって
+        // 本来のカートリッジ読み書きを中断すべきではないため、ここでは
+        // 黙って諦める(呼び出し元にResultを伝播させない)。
+        let _ = result;
+    }
+}
+
+/// `--protocol-trace`で記録したNDJSONトレースを読み込み、`read_byte`
+/// 呼び出しの結果だけを取り出して記録順に並べたバイト列を返す。このボード
+/// には`Board`のようなトレイトが存在せず`CubicStyleBoard`一つだけの
+/// 具象型なので、差し替え可能な`MockBoard`を新設するのは実機コードとの
+/// 一貫性を崩す過剰な構造変更になる -- 代わりに、トレースそのものから
+/// 「その時どんなバイト列が読み出されたか」を再構築するオフライン再生
+/// として実装する。ユーザから送られたトレースを手元で読み出し内容と
+/// 突き合わせ、バグ報告のダンプを実機なしで再現するのに使える。
+pub fn replay_trace(path: &str) -> Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    let mut bytes = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry: Value = serde_json::from_str(&line)
+            .map_err(|e| anyhow::anyhow!("malformed trace line {:?}: {}", line, e))?;
+
+        if entry["call"] == "read_byte" {
+            let result = entry["result"].as_u64().ok_or_else(|| {
+                anyhow::anyhow!("malformed trace line, read_byte result is not a number: {}", line)
+            })?;
+
+            bytes.push(result as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+// CUBIC STYLEの拡張ボードはGPIO+SPI(MCP23X08経由のパラレルバス)で
+// カートリッジと通信しており、UART/シリアル接続ではないため、ボーレートという
+// 概念自体が存在しない。将来シリアル接続の拡張ボードに対応する場合は、
+// この構造体とは別に専用のボード実装を追加し、`--baud` はそちらにのみ
+// 公開すべき。
 pub struct CubicStyleBoard {
     gpio: Gpio,
     spi: Spi,
@@ -61,10 +170,34 @@ pub struct CubicStyleBoard {
 
     addr: [OutputPin; 16],
     data_dir: DataDir,
+
+    trace: Option<ProtocolTracer>,
 }
 
+// CUBIC STYLEの拡張ボードは素のMCP23X08(GPIO拡張チップ)をSPI経由で
+// 叩いているだけで、間にマイコンやバージョン付きのプロトコルは存在しない
+// -- つまり「ファームウェアバージョン」という概念自体がこのボードには
+// 実在しない。バルク読み出しや速度制御といった機能もファームウェア側の
+// 実装ではなく、このホスト側コード(`CubicStyleBoard`自体)が1バイトずつ
+// 逐次発行しているため、バージョンによる機能ゲーティングも該当しない。
+// 将来マイコン搭載の拡張ボードに対応する場合は、専用のボード実装を
+// 追加してそちらでのみ本物のハンドシェイクを行うべき。
+pub const FIRMWARE_VERSION: &str = "n/a (direct GPIO/MCP23X08 bus, no onboard firmware)";
+
 impl CubicStyleBoard {
+    /// このボードにはバージョン付きのファームウェアプロトコルが存在しない
+    /// ため、常に固定の説明文字列を返す。`Info`等での表示用。
+    pub fn firmware_version(&self) -> &'static str {
+        FIRMWARE_VERSION
+    }
+
     pub fn new() -> Result<Self> {
+        Self::new_with_warmup(DEFAULT_WARMUP_MS)
+    }
+
+    /// `warmup_ms`だけ電源投入後の最初のトランザクション前に待機する。
+    /// RTC/EEPROM搭載カートリッジなど、直後の読み出しが化けるものに有効。
+    pub fn new_with_warmup(warmup_ms: u64) -> Result<Self> {
         let gpio = Gpio::new()?;
 
         let rd = (&gpio).get(Pin::Rd as u8)?.into_output();
@@ -90,6 +223,11 @@ impl CubicStyleBoard {
             (&gpio).get(Pin::Addr15 as u8)?.into_output(),
         ];
 
+        if warmup_ms > 0 {
+            println!("warming up for {}ms before the first transaction", warmup_ms);
+            sleep(Duration::from_millis(warmup_ms));
+        }
+
         Ok(Self {
             gpio,
             spi: Spi::new(Bus::Spi0, SlaveSelect::Ss1, 4000000, Mode::Mode0)?,
@@ -99,9 +237,33 @@ impl CubicStyleBoard {
             rst,
             addr,
             data_dir: DataDir::Input,
+            trace: None,
         })
     }
 
+    /// このボードへの以降の`set_addr`/`read_byte`/`write_byte`/
+    /// `enable_ram`/`disable_ram`呼び出しを、`path`にNDJSON形式で記録
+    /// するようにする。`path`が`None`の場合は標準エラーに出す。新しい
+    /// マッパーの実装中や、ユーザから送られた不審なダンプの原因切り分け
+    /// に使う想定で、バグ報告にそのまま添付できる。デフォルトでは無効。
+    pub fn enable_protocol_trace(&mut self, path: Option<&str>) -> Result<()> {
+        self.trace = Some(match path {
+            Some(path) => ProtocolTracer::to_file(path)?,
+            None => ProtocolTracer::to_stderr(),
+        });
+
+        Ok(())
+    }
+
+    fn trace_call(&mut self, call: &str, args: &str, result: &str) {
+        if let Some(tracer) = &mut self.trace {
+            tracer.record(&format!(
+                "{{\"call\":\"{}\",\"args\":{},\"result\":{}}}",
+                call, args, result
+            ));
+        }
+    }
+
     pub fn init(&mut self) -> Result<()> {
         self.rd.set_high();
         self.wr.set_high();
@@ -117,6 +279,14 @@ impl CubicStyleBoard {
         Ok(())
     }
 
+    /// `adapter`固有の初期化ハンドシェイクを`init()`の直後に実行する。
+    /// `Direct`(素のカートリッジ直結)は追加のシーケンスを必要としない。
+    pub fn init_adapter(&mut self, adapter: AdapterKind) -> Result<()> {
+        match adapter {
+            AdapterKind::Direct => Ok(()),
+        }
+    }
+
     pub fn set_addr(&mut self, addr: u16) {
         for i in 0..16 {
             let pin = &mut self.addr[i];
@@ -126,6 +296,8 @@ impl CubicStyleBoard {
                 pin.set_low();
             }
         }
+
+        self.trace_call("set_addr", &format!("{{\"addr\":{}}}", addr), "null");
     }
 
     pub fn read_byte(&mut self) -> Result<u8> {
@@ -140,9 +312,65 @@ impl CubicStyleBoard {
         self.set_read(false);
         self.set_cs(false);
 
+        self.trace_call("read_byte", "{}", &data.to_string());
+
         Ok(data)
     }
 
+    // MBC1のRAMイネーブルラッチは実際には下位4ビットしか見ておらず、
+    // 0x?Aならどの値でも有効化として扱う実装が多い(MBC3/MBC5も同様の
+    // 実装が広く出回っている)。ここでは仕様書通りの正準値0x0Aを書くが、
+    // 無効化側は下位ニブルが0x0Aと衝突しない0x00を使うことが重要
+    // -- 例えば0x1Aのような値を書くと、下位ニブルだけを見るラッチ実装の
+    // 一部の(特にブートレグ)カートリッジで無効化に失敗する。
+    pub fn enable_ram(&mut self) -> Result<()> {
+        self.set_addr(0x0000);
+        self.write_byte(0x0A)?;
+
+        self.trace_call("enable_ram", "{}", "null");
+
+        Ok(())
+    }
+
+    pub fn disable_ram(&mut self) -> Result<()> {
+        self.set_addr(0x0000);
+        self.write_byte(0x00)?;
+
+        self.trace_call("disable_ram", "{}", "null");
+
+        Ok(())
+    }
+
+    // 一部のGBA世代のGB互換カート(マルチブート用の切り替え可能な
+    // デュアルモードカート)は、通常のGBカートリッジスロット配線で
+    // アクセスする前に、GBモードへの切り替えを促す書き込みシーケンスを
+    // 要求すると報告されている。このリポジトリの実機ではこの種のカート
+    // での検証を行っていないため、ここで発行するシーケンス(0x0000への
+    // 0x00 -> 0x01の連続書き込み)は公開情報からの推測であり、正準的な
+    // 仕様として保証するものではない。`--gba-gb-mode`が明示された場合のみ
+    // 発行し、既定では一切触れない。
+    pub fn select_gba_gb_mode(&mut self) -> Result<()> {
+        self.set_addr(0x0000);
+        self.write_byte(0x00)?;
+
+        self.set_addr(0x0000);
+        self.write_byte(0x01)?;
+
+        self.trace_call("select_gba_gb_mode", "{}", "null");
+
+        Ok(())
+    }
+
+    pub fn reset(&mut self) -> Result<()> {
+        self.rst.set_low();
+
+        sleep(Duration::from_millis(1));
+
+        self.rst.set_high();
+
+        Ok(())
+    }
+
     pub fn write_byte(&mut self, val: u8) -> Result<()> {
         self.mcp_into_output()?;
 
@@ -155,6 +383,8 @@ impl CubicStyleBoard {
         self.set_write(false);
         self.set_cs(false);
 
+        self.trace_call("write_byte", &format!("{{\"val\":{}}}", val), "null");
+
         Ok(())
     }
 
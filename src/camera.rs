@@ -0,0 +1,49 @@
+use crate::board::CubicStyleBoard;
+use crate::mbc::write_ram_range;
+use crate::rom::{RomHeader, RAM_BANK_SIZE};
+use anyhow::{bail, Result};
+
+// GAME BOY CAMERA(MAC-GBD)の128KB SRAMのレイアウト。公開されている解析
+// 情報に基づく: 先頭8KB(バンク0)はゲーム内設定/顔登録/アルバム管理用の
+// 領域で、残り15バンク分を写真1枚あたり0x1000バイトずつに区切って
+// 最大30枚を保存する(0x2000 + 30 * 0x1000 = 0x20000 = 128KB)。
+// このリポジトリ内で実機ダンプと突き合わせた検証はまだ行っていない。
+
+/// 写真1枚あたりのタイル/パレットデータのバイト数。
+pub const PHOTO_SLOT_SIZE: usize = 0x1000;
+
+/// アルバムに保存できる写真の最大枚数。
+pub const PHOTO_SLOT_COUNT: usize = 30;
+
+/// 写真領域の開始オフセット。バンク0(ゲーム内設定領域)の直後。
+pub const PHOTO_AREA_BASE: usize = RAM_BANK_SIZE;
+
+/// `slot`(0-29)のSRAM上のバイト範囲を`(開始オフセット, サイズ)`で返す。
+pub fn photo_slot_range(slot: usize) -> Result<(usize, usize)> {
+    if slot >= PHOTO_SLOT_COUNT {
+        bail!(
+            "invalid photo slot {}: must be 0-{}",
+            slot,
+            PHOTO_SLOT_COUNT - 1
+        );
+    }
+
+    Ok((PHOTO_AREA_BASE + slot * PHOTO_SLOT_SIZE, PHOTO_SLOT_SIZE))
+}
+
+/// `slot`番目の写真のタイル/パレットデータを`fill_byte`で埋めて消去する。
+/// 写真の実データのみを書き換え、他のスロットのバイト範囲には一切触れ
+/// ない。バンク0にある「使用中」を示すアルバム管理テーブルの正確な
+/// ビット配置はこのリポジトリでは未検証のため書き換えないので、ゲーム上は
+/// 新しい写真で上書きされるまでこのスロットが使用中のまま表示され続ける
+/// 可能性がある。
+pub fn clear_photo_slot(
+    board: &mut CubicStyleBoard,
+    header: &RomHeader,
+    slot: usize,
+    fill_byte: u8,
+) -> Result<()> {
+    let (start, size) = photo_slot_range(slot)?;
+
+    write_ram_range(board, header, start, &vec![fill_byte; size])
+}
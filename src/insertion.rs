@@ -0,0 +1,99 @@
+// `--loop`時のカートリッジ抜き差し検出用ステートマシン。
+// ロゴ有効判定を1秒間隔でポーリングする呼び出し元から、
+// 半刺し状態でのロゴ誤読を避けるためデバウンスを行う。
+
+const DEFAULT_DEBOUNCE_TICKS: u32 = 2;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum State {
+    Absent,
+    Debouncing(u32),
+    Present,
+}
+
+pub struct InsertionDetector {
+    state: State,
+    debounce_ticks: u32,
+}
+
+impl InsertionDetector {
+    pub fn new() -> Self {
+        Self::with_debounce_ticks(DEFAULT_DEBOUNCE_TICKS)
+    }
+
+    pub fn with_debounce_ticks(debounce_ticks: u32) -> Self {
+        Self {
+            state: State::Absent,
+            debounce_ticks: debounce_ticks.max(1),
+        }
+    }
+
+    /// ロゴが有効かどうかを1ティック分渡し、新規挿入が確定した瞬間だけ`true`を返す。
+    pub fn poll(&mut self, logo_valid: bool) -> bool {
+        let was_present = self.state == State::Present;
+
+        self.state = match (self.state, logo_valid) {
+            (State::Absent, true) => State::Debouncing(1),
+            (State::Debouncing(n), true) if n + 1 >= self.debounce_ticks => State::Present,
+            (State::Debouncing(n), true) => State::Debouncing(n + 1),
+            (_, false) => State::Absent,
+            (state, true) => state,
+        };
+
+        !was_present && self.state == State::Present
+    }
+}
+
+impl Default for InsertionDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_insertion_only_once_debounce_ticks_have_elapsed() {
+        let mut detector = InsertionDetector::with_debounce_ticks(3);
+
+        assert!(!detector.poll(true), "first tick should still be debouncing");
+        assert!(!detector.poll(true), "second tick should still be debouncing");
+        assert!(detector.poll(true), "third consecutive tick should confirm insertion");
+    }
+
+    #[test]
+    fn does_not_report_insertion_again_while_still_present() {
+        let mut detector = InsertionDetector::with_debounce_ticks(2);
+
+        assert!(!detector.poll(true));
+        assert!(detector.poll(true));
+        assert!(!detector.poll(true), "already-present cartridge should not re-trigger");
+        assert!(!detector.poll(true));
+    }
+
+    #[test]
+    fn a_dropout_resets_the_debounce_counter() {
+        let mut detector = InsertionDetector::with_debounce_ticks(3);
+
+        assert!(!detector.poll(true));
+        assert!(!detector.poll(false), "a single bad read should restart debouncing");
+        assert!(!detector.poll(true), "first tick after the dropout");
+        assert!(!detector.poll(true), "second tick after the dropout");
+        assert!(detector.poll(true), "third tick after the dropout should confirm insertion");
+    }
+
+    #[test]
+    fn reports_insertion_again_after_removal_and_reinsertion() {
+        let mut detector = InsertionDetector::with_debounce_ticks(1);
+
+        assert!(!detector.poll(true));
+        assert!(detector.poll(true), "first insertion");
+        assert!(!detector.poll(true), "still present");
+
+        assert!(!detector.poll(false), "removal");
+        assert!(!detector.poll(true));
+        assert!(detector.poll(true), "reinsertion should be reported again");
+    }
+}
@@ -1,4 +1,11 @@
 pub mod mbc;
 pub mod board;
+pub mod camera;
+pub mod insertion;
+pub mod manifest;
+pub mod messages;
+pub mod report;
 pub mod rom;
+pub mod slots;
+pub mod split;
 pub mod utils;
@@ -0,0 +1,102 @@
+use crate::rom::RomHeader;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA_VERSION: u32 = 1;
+
+/// `Read --sign`が書き出す、ダンプの改ざん検知用メタデータ。JSONとして
+/// ROMファイルの隣に保存され、`Verify --manifest`で再検証できる。
+/// スキーマは今のところ平坦なJSONオブジェクトで、`schema_version`だけ
+/// 将来の互換性判断に使う。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DumpManifest {
+    pub schema_version: u32,
+    pub rom_path: String,
+    pub sha256: String,
+    pub tool_version: String,
+    pub unix_time: u64,
+    pub title: String,
+    pub mbc_type: String,
+    pub rom_size: usize,
+}
+
+impl DumpManifest {
+    pub fn new(rom_path: &str, sha256: String, header: &RomHeader) -> Self {
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            rom_path: rom_path.to_string(),
+            sha256,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            unix_time,
+            title: header.title_str(),
+            mbc_type: header.mbc_type.to_string(),
+            rom_size: header.rom_size,
+        }
+    }
+
+    pub fn manifest_path(rom_path: &str) -> String {
+        format!("{}.manifest.json", rom_path)
+    }
+
+    pub fn write(&self, path: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// `rom_path`が指すファイルを再度ハッシュ化し、記録された`sha256`と
+    /// 一致するか検証する。ファイルの実体自体は`rom_path`の隣にある
+    /// 前提で、マニフェスト単体では改ざん検知の役に立たない
+    /// (署名鍵を使った検証ではない)ことに注意。
+    pub fn verify(&self) -> Result<()> {
+        let path = Path::new(&self.rom_path);
+
+        if !path.exists() {
+            bail!("referenced rom file {:?} does not exist", path);
+        }
+
+        let mut file = fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = [0u8; 0x10000];
+
+        loop {
+            let n = file.read(&mut buffer)?;
+
+            if n == 0 {
+                break;
+            }
+
+            hasher.update(&buffer[..n]);
+        }
+
+        let digest = format!("{:x}", hasher.finalize());
+
+        if digest != self.sha256 {
+            bail!(
+                "hash mismatch: manifest records {}, recomputed {}",
+                self.sha256,
+                digest
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,41 +1,426 @@
 use crate::board::CubicStyleBoard;
-use crate::rom::{MbcType, RomHeader};
-use anyhow::Result;
+use crate::rom::{MbcType, RomHeader, MBC2_RAM_SIZE, MBC7_EEPROM_SIZE, NINTENDO_LOGO, RAM_BANK_SIZE};
+use crate::utils::{analyze_line_continuity, LineContinuityReport};
+use anyhow::{bail, Result};
 use std::io::{stdin, ErrorKind, Read, Seek, SeekFrom};
 use std::time::Duration;
 use std::{io, thread};
 
+/// ROMバンク切り替えレジスタへの書き込み頻度。まれに、電圧降下や
+/// バンク切り替えICの相性によって選択したバンクを保持し続けられない
+/// カートリッジがあり、そうした個体は`PerChunk`で各`read()`呼び出し
+/// (チャンク)の先頭で毎回同じバンク値を再送することで救える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BankSelectStrategy {
+    OncePerBank,
+    PerChunk,
+}
+
+impl Default for BankSelectStrategy {
+    fn default() -> Self {
+        BankSelectStrategy::OncePerBank
+    }
+}
+
 pub trait MbcReader: Read {
     fn size(&self) -> usize;
     fn status(&self) -> String;
+
+    /// 固定バンク(0x0000-0x3FFF)のNintendoロゴを読み直し、切り替え可能な
+    /// バンクの状態には触れずに配線の緩みを検出する。長時間のダンプ中に
+    /// 定期的に呼び出すための診断用フック。
+    fn verify_logo(&mut self) -> Result<bool>;
+
+    /// `--keep-going`使用時に0xFFで埋めて読み飛ばした論理アドレス
+    /// (このリーダー自身の通し番号、バンク切り替え前の実カートアドレス
+    /// ではない)の一覧。`--keep-going`を使っていない、または一度も
+    /// 失敗していなければ空のまま。
+    fn faults(&self) -> &[u32] {
+        &[]
+    }
+
+    /// `bank`番の切り替え可能バンク内、オフセット`addr`(0x0000-0x3FFF)へ
+    /// 読み出し位置を移動する。以降の`read()`はそこから連続して読む。
+    /// 明示的な再開位置指定(`--resume-from-bank`相当)や範囲読み出しなど、
+    /// 先頭から順に読み進める以外の方法で位置を決めたい機能の共通基盤。
+    /// バンク切り替えレジスタへ実際に書き込むため、対応するマッパーの
+    /// 読み出し器のみが上書きする -- 一発ラッチ式のM161や、実際の
+    /// マッパーが確定していないUnknownMbcReaderのように、任意の位置への
+    /// シークが安全に行えないリーダーは既定の未対応エラーのままにする。
+    fn seek_to(&mut self, _bank: u16, _addr: u16) -> Result<()> {
+        bail!("seek_to is not supported for this reader")
+    }
+
+    /// `--verify-bank-switch`使用時、切り替え可能ウィンドウの先頭
+    /// (0x4000)を切り替え前後でサンプリングし、同じ値のままだった
+    /// (=切り替えが効いていない疑いがある)バンク番号の一覧。
+    /// `--verify-bank-switch`を使っていない、または一度もそうした疑いが
+    /// なければ空のまま。切り替えレジスタへ実際に書き込むリーダーのみが
+    /// 上書きする。
+    fn bank_switch_faults(&self) -> &[u8] {
+        &[]
+    }
+
+    /// `--retry-whole-bank`使用時、バンク切り替え直後に2回連続で同じ内容が
+    /// 読めるまでバンクを再選択して丸ごと読み直した(=1回目の読み出しが
+    /// 不安定だった)バンク番号の一覧。`--retry-whole-bank`を使っていない、
+    /// または一度も不安定にならなければ空のまま。丸ごと読み直しに対応する
+    /// リーダーのみが上書きする。
+    fn unstable_banks(&self) -> &[u8] {
+        &[]
+    }
+}
+
+/// `--keep-going`時、`read_byte`が失敗したら警告を出して0xFFで埋め、
+/// 呼び出し元がダンプを継続できるようにする。`keep_going`が偽の場合は
+/// 従来通りエラーをそのまま伝播する。壊れたバンク切り替えレジスタへの
+/// 書き込み(制御線の異常)まではこの関数の対象外で、あくまで純粋な
+/// データ読み出しの失敗だけを救済する。
+fn read_byte_or_fill(
+    board: &mut CubicStyleBoard,
+    logical_addr: u32,
+    keep_going: bool,
+    faults: &mut Vec<u32>,
+) -> io::Result<u8> {
+    match board.read_byte() {
+        Ok(byte) => Ok(byte),
+        Err(e) if keep_going => {
+            eprintln!(
+                "warning: read failed at offset 0x{:06X} ({:#}); filling with 0xFF and continuing (--keep-going)",
+                logical_addr, e
+            );
+            faults.push(logical_addr);
+            Ok(0xFF)
+        }
+        Err(e) => Err(io::Error::new(ErrorKind::BrokenPipe, e)),
+    }
+}
+
+fn read_logo_at_fixed_bank(board: &mut CubicStyleBoard) -> Result<bool> {
+    let mut logo = [0u8; 0x0030];
+
+    for (i, byte) in logo.iter_mut().enumerate() {
+        board.set_addr(0x0104 + i as u16);
+        *byte = board.read_byte()?;
+    }
+
+    Ok(logo == NINTENDO_LOGO)
+}
+
+// データ線用サンプル(先頭2KB、ヘッダや実行コードを含むため単調になり
+// にくい)の範囲。
+const SELFTEST_DATA_SAMPLE_RANGE: u16 = 0x0800;
+
+// アドレス線用サンプルの基準アドレス。それぞれについて0-13の全ビットを
+// 反転させたペアを読み、`analyze_line_continuity`が両方を確実に見つけ
+// られるようにする。
+const SELFTEST_ADDRESS_PROBE_BASES: &[u16] = &[0x0000, 0x0100, 0x0400, 0x1000, 0x2000, 0x3000];
+
+/// `--selftest`用に、固定バンク(0x0000-0x3FFF)からサンプリングし、
+/// アドレス/データ線の断線・半田不良の疑いを診断する。
+pub fn run_line_selftest(board: &mut CubicStyleBoard) -> Result<LineContinuityReport> {
+    let mut samples = Vec::new();
+
+    for addr in 0..SELFTEST_DATA_SAMPLE_RANGE {
+        board.set_addr(addr);
+        samples.push((addr, board.read_byte()?));
+    }
+
+    for &base in SELFTEST_ADDRESS_PROBE_BASES {
+        board.set_addr(base);
+        samples.push((base, board.read_byte()?));
+
+        for bit in 0..14u16 {
+            let addr = base ^ (1 << bit);
+            board.set_addr(addr);
+            samples.push((addr, board.read_byte()?));
+        }
+    }
+
+    Ok(analyze_line_continuity(&samples))
+}
+
+/// バンク切り替え直後、指定回数だけ`addr`を読み捨てる。長い/劣化した
+/// 配線ではバス容量の充放電が追いつかず、切り替え直後の1バイト目だけが
+/// 化けることがある。`settle_reads`が0なら何もしない。
+fn settle(board: &mut CubicStyleBoard, addr: u16, settle_reads: u32) -> io::Result<()> {
+    for _ in 0..settle_reads {
+        board.set_addr(addr);
+        board
+            .read_byte()
+            .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+    }
+
+    Ok(())
+}
+
+/// `--verify-bank-switch`用に、切り替え可能ウィンドウの先頭(0x4000)を
+/// サンプリングし、直前の切り替えで記録した値と比較する。同じ値であれば
+/// (=バンクが変わったのに中身が変わっていなければ)切り替え失敗の疑いが
+/// あるとして`new_bank`を`faults`へ記録する。「バンク番号が埋め込まれた
+/// 既知のオフセットを見る」のではなく、「切り替え前後で領域の中身が
+/// 本当に変わったか」を汎用的に確認する、より単純な方のアプローチ。
+fn verify_bank_switch(
+    board: &mut CubicStyleBoard,
+    new_bank: u8,
+    last_sample: &mut Option<u8>,
+    faults: &mut Vec<u8>,
+) -> io::Result<()> {
+    board.set_addr(0x4000);
+    let sample = board
+        .read_byte()
+        .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+    if *last_sample == Some(sample) {
+        eprintln!(
+            "warning: bank switch to bank {} looks like it may not have taken effect \
+             (0x4000 read back the same byte as the previous bank); this can indicate a \
+             marginal cartridge connection or an unsupported banking quirk",
+            new_bank
+        );
+        faults.push(new_bank);
+    }
+
+    *last_sample = Some(sample);
+
+    Ok(())
+}
+
+/// `--retry-whole-bank`用に、切り替え可能ウィンドウ(`base`から0x4000バイト)を
+/// 丸ごと読み出す。バンク切り替え直後の安定性確認と、実際にダンプへ使う
+/// 内容の取得の両方に使う共通ヘルパー。
+fn read_bank_window(board: &mut CubicStyleBoard, base: u16, len: u16) -> io::Result<Vec<u8>> {
+    let mut data = vec![0u8; len as usize];
+
+    for (i, byte) in data.iter_mut().enumerate() {
+        board.set_addr(base + i as u16);
+        *byte = board
+            .read_byte()
+            .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+    }
+
+    Ok(data)
+}
+
+/// バンク切り替えレジスタ(0x2000)へ直接書き込みながら、切り替え可能
+/// ウィンドウ(0x4000-0x7FFF)の先頭を各バンクでサンプリングし、上位バンクが
+/// 下位バンクの繰り返し(ミラー)になっている境目を探すことで、物理的な
+/// ROMサイズを推定する。ヘッダのROMサイズバイトが壊れている/偽装された
+/// ブートレグカートリッジ向け。0x2000への1バイト書き込みでバンクを選ぶ
+/// 前提のため、MBC5の9ビット目のような256バンク超の拡張バンキングには
+/// 対応しない(最大256バンク=4MBまで)。
+fn probe_physical_rom_size(board: &mut CubicStyleBoard, header: &RomHeader) -> Result<usize> {
+    const SAMPLE_LEN: usize = 0x0020;
+
+    let declared_banks = header.rom_bank_count().max(2).min(256);
+
+    // バンク0は固定領域(0x0000-0x3FFF)であり、0x2000への書き込みでは
+    // 選択できない(多くのマッパーはバンク0指定をバンク1として扱う)ため、
+    // バンク1から順にサンプリングする。
+    let mut samples = Vec::with_capacity(declared_banks - 1);
+
+    for bank in 1..declared_banks {
+        board.set_addr(0x2000);
+        board.write_byte(bank as u8)?;
+
+        let mut chunk = [0u8; SAMPLE_LEN];
+        for (i, byte) in chunk.iter_mut().enumerate() {
+            board.set_addr(0x4000 + i as u16);
+            *byte = board.read_byte()?;
+        }
+
+        samples.push(chunk);
+    }
+
+    let bank_count = samples.len();
+
+    for period in 1..bank_count {
+        let mirrored = (0..bank_count).all(|i| samples[i] == samples[i % period]);
+
+        if mirrored {
+            // samples[0]がバンク1なので、period個の連続バンクがユニーク
+            // ということは、実バンク数はperiod+1(バンク0を含む)。
+            return Ok((period + 1) * 0x4000);
+        }
+    }
+
+    Ok(declared_banks * 0x4000)
+}
+
+/// `new_mbc_reader`のフラグ集。`--mbc`や`--gba-gb-mode`などCLIオプションが
+/// 増えるたびに引数を1個ずつ足していくと、呼び出し側(main.rsの各サブ
+/// コマンドやgui.rs)を全部書き換えないと気付かれずコンパイルが通って
+/// しまう(位置引数の型が同じ`bool`/`Option<T>`ばかりのため)。フィールド名
+/// 付きのこの構造体を経由することで、追加時に呼び出し側の更新漏れが
+/// コンパイルエラーとして必ず表面化する。
+#[derive(Debug, Clone, Default)]
+pub struct NewMbcReaderOptions {
+    pub settle_reads: u32,
+    pub probe_rom_size: bool,
+    pub bank_select_strategy: BankSelectStrategy,
+    pub m161_override: bool,
+    pub keep_going: bool,
+    pub mbc_override: Option<MbcType>,
+    pub trust_header_sizes: bool,
+    pub verify_bank_switch: bool,
+    pub retry_whole_bank: Option<u32>,
+    pub gba_gb_mode: bool,
 }
 
 pub fn new_mbc_reader<'a>(
     board: &'a mut CubicStyleBoard,
+    options: NewMbcReaderOptions,
 ) -> Result<(Box<dyn MbcReader + 'a>, RomHeader)> {
-    let header = {
+    let NewMbcReaderOptions {
+        settle_reads,
+        probe_rom_size,
+        bank_select_strategy,
+        m161_override,
+        keep_going,
+        mbc_override,
+        trust_header_sizes,
+        verify_bank_switch,
+        retry_whole_bank,
+        gba_gb_mode,
+    } = options;
+
+    if gba_gb_mode {
+        board.select_gba_gb_mode()?;
+    }
+
+    let mut header = {
         let mut reader = RomHeaderReader::new(board);
 
         RomHeader::from_reader(&mut reader)
     }?;
 
+    // バンク切り替えを信用する前に、固定領域(バンク0)自体が正しく
+    // 読めているか確認する。ここが壊れていれば、原因はマッパー種別では
+    // なく接続不良である可能性が高い。
+    if !header.is_valid_logo() {
+        bail!(
+            "bank 0 (0x0000-0x3FFF) did not contain a valid Nintendo logo; \
+             this points at a connection problem, not an unsupported mapper \
+             -- reseat the cartridge and try again{}",
+            if gba_gb_mode {
+                ""
+            } else {
+                " (if this is a GBA-era GB-compatible cartridge, try --gba-gb-mode)"
+            }
+        );
+    }
+
+    if let Some(forced) = mbc_override {
+        println!(
+            "--mbc override: treating this cartridge as {} regardless of the header byte ({})",
+            forced, header.mbc_type
+        );
+
+        header.mbc_type = forced;
+    }
+
+    for warning in header.reconcile(trust_header_sizes) {
+        eprintln!("warning: {}", warning);
+    }
+
+    if probe_rom_size {
+        let probed = probe_physical_rom_size(board, &header)?;
+
+        println!(
+            "probed ROM size: {} bytes (header declares {} bytes){}",
+            probed,
+            header.rom_size,
+            if probed == header.rom_size {
+                ""
+            } else {
+                " -- using the probed size"
+            }
+        );
+
+        header.rom_size = probed;
+    }
+
+    if m161_override {
+        return Ok((Box::new(M161Reader::new(board, header, keep_going)?), header));
+    }
+
     Ok((
         match header.mbc_type {
-            MbcType::RomOnly => Box::new(RomOnlyReader::new(board, header)),
+            MbcType::RomOnly => Box::new(RomOnlyReader::new(board, header, keep_going)),
             MbcType::Mbc1 | MbcType::Mbc1Ram | MbcType::Mbc1RamBattery => {
-                Box::new(Mbc1Reader::new(board, header))
+                Box::new(Mbc1Reader::new(
+                    board,
+                    header,
+                    settle_reads,
+                    bank_select_strategy,
+                    keep_going,
+                    verify_bank_switch,
+                    retry_whole_bank,
+                ))
             }
-            MbcType::Mbc2 | MbcType::Mbc2Battery => Box::new(Mbc2Reader::new(board, header)),
+            MbcType::Mbc2 | MbcType::Mbc2Battery => Box::new(Mbc2Reader::new(
+                board,
+                header,
+                settle_reads,
+                bank_select_strategy,
+                keep_going,
+                verify_bank_switch,
+                retry_whole_bank,
+            )),
             MbcType::Mbc3
             | MbcType::Mbc3Ram
             | MbcType::Mbc3RamBattery
-            | MbcType::Mbc3TimerRamBattery => Box::new(Mbc3Reader::new(board, header)),
+            | MbcType::Mbc3TimerRamBattery
+            | MbcType::PocketCamera => Box::new(Mbc3Reader::new(
+                board,
+                header,
+                settle_reads,
+                bank_select_strategy,
+                keep_going,
+                verify_bank_switch,
+                retry_whole_bank,
+            )),
             MbcType::Mbc5
             | MbcType::Mbc5Ram
             | MbcType::Mbc5RamBattery
             | MbcType::Mbc5Rumble
             | MbcType::Mbc5RumbleRam
-            | MbcType::Mbc5RumbleRamBattery => Box::new(Mbc5Reader::new(board, header)),
+            | MbcType::Mbc5RumbleRamBattery => Box::new(Mbc5Reader::new(
+                board,
+                header,
+                settle_reads,
+                bank_select_strategy,
+                keep_going,
+                verify_bank_switch,
+                retry_whole_bank,
+            )),
+            MbcType::Mbc6 => Box::new(Mbc6Reader::new(
+                board,
+                header,
+                settle_reads,
+                bank_select_strategy,
+                keep_going,
+                verify_bank_switch,
+                retry_whole_bank,
+            )),
+            MbcType::Unknown(b) => {
+                eprintln!(
+                    "warning: cartridge type byte {:#04X} is not a mapper this reader recognizes; \
+                     attempting a conservative best-effort ROM-only dump (bank switches are \
+                     speculatively written to both MBC1- and MBC5-style registers). If you know \
+                     the real mapper, force it with --mbc",
+                    b
+                );
+
+                Box::new(UnknownMbcReader::new(board, header, b, keep_going))
+            }
+            MbcType::Mbc7SensorRumbleRamBattery => {
+                bail!(
+                    "{} ROM dumping is not yet implemented (only the save data path via \
+                     --eeprom is supported for this mapper)",
+                    header.mbc_type
+                );
+            }
             t => {
                 unimplemented!("unimplemented mbc: {:?}", t);
             }
@@ -56,23 +441,549 @@ pub fn new_repl_mbc_reader<'a>(
     Ok((Box::new(ReplReader::new(board, header)), header))
 }
 
-pub struct RomHeaderReader<'a> {
+// 実機のバンク切り替えに合わせて16KB(0x4000)単位。バンク0
+// (0x0000-0x3FFF)は固定領域だが、他のバンクと同じ16KB窓として
+// 数えるので特別扱いは不要 -- 各`MbcReader`実装が自身の`read`の中で
+// 固定/切り替えの区別を既に行っている。
+pub const ROM_BANK_SIZE: usize = 0x4000;
+
+/// `MbcReader`を16KBバンク単位で消費するイテレータ。バンク切り替え自体は
+/// 各`MbcReader`実装(`Mbc1Reader`など)の`read`が内部で行うため、ここでは
+/// 単純に固定サイズで読み進めるだけでよい。
+pub struct RomBanks<'a> {
+    reader: &'a mut dyn MbcReader,
+    remaining: usize,
+    next_bank: u32,
+}
+
+impl<'a> RomBanks<'a> {
+    fn new(reader: &'a mut dyn MbcReader) -> Self {
+        let remaining = reader.size();
+
+        Self {
+            reader,
+            remaining,
+            next_bank: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for RomBanks<'a> {
+    type Item = Result<(u32, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let take = self.remaining.min(ROM_BANK_SIZE);
+        let mut buf = vec![0u8; take];
+
+        if let Err(e) = self.reader.read_exact(&mut buf) {
+            self.remaining = 0;
+
+            return Some(Err(e.into()));
+        }
+
+        let bank = self.next_bank;
+        self.next_bank += 1;
+        self.remaining -= take;
+
+        Some(Ok((bank, buf)))
+    }
+}
+
+pub fn rom_banks<'a>(reader: &'a mut dyn MbcReader) -> RomBanks<'a> {
+    RomBanks::new(reader)
+}
+
+/// `reader`を末尾まで読み切り、`Vec<u8>`として返す。ライブラリとして
+/// 組み込む利用者がファイルを経由せずバイト列を直接扱えるようにする。
+/// `cap`を指定すると、想定外に大きいカートリッジ(バンク数の誤判定など)
+/// でメモリを食い潰さないよう、超過時にエラーで打ち切る。`progress`には
+/// これまでに読んだバイト数が呼び出しごとに渡される。
+fn read_to_vec<R: Read + ?Sized>(
+    reader: &mut R,
+    cap: Option<usize>,
+    mut progress: impl FnMut(usize),
+) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 0x1000];
+
+    loop {
+        let n = reader.read(&mut chunk)?;
+
+        if n == 0 {
+            break;
+        }
+
+        if let Some(cap) = cap {
+            if buf.len() + n > cap {
+                bail!(
+                    "read exceeded the {}-byte cap; the cartridge may be mis-sized \
+                     or the reader is over-delivering",
+                    cap
+                );
+            }
+        }
+
+        buf.extend_from_slice(&chunk[..n]);
+        progress(buf.len());
+    }
+
+    Ok(buf)
+}
+
+pub fn read_rom_to_vec(
+    reader: &mut dyn MbcReader,
+    cap: Option<usize>,
+    progress: impl FnMut(usize),
+) -> Result<Vec<u8>> {
+    read_to_vec(reader, cap, progress)
+}
+
+pub fn read_ram_to_vec(
+    reader: &mut dyn RamReader,
+    cap: Option<usize>,
+    progress: impl FnMut(usize),
+) -> Result<Vec<u8>> {
+    read_to_vec(reader, cap, progress)
+}
+
+/// バンク境界をまたぐ`data`を、0x4000のRAMバンクレジスタを切り替えながら
+/// 0xA000-0xBFFFのSRAM窓へ書き込む。`WriteRam`とGUIの復元ボタンの両方から
+/// 使われる共通実装で、`on_progress`にはこれまでに書き込んだバイト数と
+/// 総バイト数が渡される。
+pub fn write_ram_banked(
+    board: &mut CubicStyleBoard,
+    data: &[u8],
+    header: &RomHeader,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<()> {
+    let is_mbc2 = matches!(header.mbc_type, MbcType::Mbc2 | MbcType::Mbc2Battery);
+    let bank_size = RAM_BANK_SIZE.min(header.ram_size_bytes().max(1));
+    let total = data.len();
+
+    let mut bank = 0u8;
+
+    board.set_addr(0x4000);
+    board.write_byte(bank)?;
+
+    for (i, &byte) in data.iter().enumerate() {
+        if i != 0 && i % bank_size == 0 {
+            bank += 1;
+
+            board.set_addr(0x4000);
+            board.write_byte(bank)?;
+        }
+
+        board.set_addr(0xA000 + (i % bank_size) as u16);
+        board.write_byte(if is_mbc2 { byte & 0x0F } else { byte })?;
+
+        on_progress(i + 1, total);
+    }
+
+    Ok(())
+}
+
+/// `write_ram_banked`と同じバンク切り替えロジックだが、先頭(オフセット0)
+/// からではなく`start_offset`から`data`を書き込む。RAM全体を読み戻す
+/// 必要がある通常のセーブ復元とは違い、一部の範囲だけを狙って書き換え
+/// たい場合(例: GAME BOY CAMERAの特定の写真スロットだけを消去する)に使う。
+pub fn write_ram_range(
+    board: &mut CubicStyleBoard,
+    header: &RomHeader,
+    start_offset: usize,
+    data: &[u8],
+) -> Result<()> {
+    let is_mbc2 = matches!(header.mbc_type, MbcType::Mbc2 | MbcType::Mbc2Battery);
+    let bank_size = RAM_BANK_SIZE.min(header.ram_size_bytes().max(1));
+
+    let mut bank = (start_offset / bank_size) as u8;
+
+    board.set_addr(0x4000);
+    board.write_byte(bank)?;
+
+    for (i, &byte) in data.iter().enumerate() {
+        let global = start_offset + i;
+        let current_bank = (global / bank_size) as u8;
+
+        if current_bank != bank {
+            bank = current_bank;
+
+            board.set_addr(0x4000);
+            board.write_byte(bank)?;
+        }
+
+        board.set_addr(0xA000 + (global % bank_size) as u16);
+        board.write_byte(if is_mbc2 { byte & 0x0F } else { byte })?;
+    }
+
+    Ok(())
+}
+
+/// MBC5フラッシュカート(最大8MB = 512バンク)が積めるROMの上限。
+/// 9bitバンクレジスタ(0x2000下位8bit+0x3000上位1bit)で表現できる範囲と一致する。
+pub const MBC5_FLASH_MAX_ROM_SIZE: usize = 0x200 * ROM_BANK_SIZE;
+
+/// `write_ram_banked`のROM版。`data`を16KBバンク単位に区切り、MBC5の
+/// 9bitバンクレジスタ(0x2000へ下位8bit、0x3000へ上位1bit)を切り替えながら
+/// 0x4000-0x7FFFの切り替え窓へ書き込む。バンク0も例外扱いせずこの窓へ
+/// 書き込む -- 0x0000-0x3FFFの固定窓は読み出し専用のミラーで、フラッシュ
+/// チップへの書き込みコマンド自体はどのバンクも切り替え窓経由で届く
+/// (実機のEverdrive/EZ-Flash等のromヘッダ書き換えと同じやり方)。
+/// `WriteRom`から使われる共通実装で、`on_progress`にはこれまでに
+/// 書き込んだバイト数と総バイト数が渡される。
+pub fn write_rom_banked_mbc5(
+    board: &mut CubicStyleBoard,
+    data: &[u8],
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<()> {
+    if data.len() > MBC5_FLASH_MAX_ROM_SIZE {
+        bail!(
+            "image is {} bytes, which exceeds the 8MB (0x{:X} byte) ceiling of MBC5's 9-bit \
+             bank register",
+            data.len(),
+            MBC5_FLASH_MAX_ROM_SIZE
+        );
+    }
+
+    let total = data.len();
+
+    for (bank, chunk) in data.chunks(ROM_BANK_SIZE).enumerate() {
+        let bank = bank as u16;
+
+        board.set_addr(0x2000);
+        board.write_byte((bank & 0xFF) as u8)?;
+
+        board.set_addr(0x3000);
+        board.write_byte(((bank >> 8) & 0b1) as u8)?;
+
+        for (i, &byte) in chunk.iter().enumerate() {
+            board.set_addr(0x4000 + i as u16);
+            board.write_byte(byte)?;
+        }
+
+        on_progress(bank as usize * ROM_BANK_SIZE + chunk.len(), total);
+    }
+
+    Ok(())
+}
+
+/// `write_rom_banked_mbc5`で書き込んだ内容を読み戻して突き合わせる。
+/// 食い違ったバンクと、そのバンク内最初の食い違いアドレスを返す
+/// (空なら全バンク一致)。
+pub fn verify_rom_banked_mbc5(
+    board: &mut CubicStyleBoard,
+    data: &[u8],
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<Option<(u16, u16)>> {
+    let total = data.len();
+
+    for (bank, chunk) in data.chunks(ROM_BANK_SIZE).enumerate() {
+        let bank = bank as u16;
+
+        board.set_addr(0x2000);
+        board.write_byte((bank & 0xFF) as u8)?;
+
+        board.set_addr(0x3000);
+        board.write_byte(((bank >> 8) & 0b1) as u8)?;
+
+        for (i, &expected) in chunk.iter().enumerate() {
+            board.set_addr(0x4000 + i as u16);
+            let got = board.read_byte()?;
+
+            if got != expected {
+                return Ok(Some((bank, i as u16)));
+            }
+        }
+
+        on_progress(bank as usize * ROM_BANK_SIZE + chunk.len(), total);
+    }
+
+    Ok(None)
+}
+
+pub trait RamReader: Read {
+    fn size(&self) -> usize;
+    fn status(&self) -> String;
+
+    /// `--ignore-ram-bank-errors`使用時に読み出しへ失敗し、埋め値で穴埋めして
+    /// 継続したRAMバンク番号の一覧(重複無し、出現順)。使っていない、または
+    /// 一度も失敗していなければ空のまま。
+    fn incomplete_banks(&self) -> &[u8] {
+        &[]
+    }
+
+    /// `--validate-nibbles`使用時、マスク前に実際に読めた高位ニブルの
+    /// 種類(重複無し、出現順)。MBC2の4bit RAMは高位ニブルが未定義
+    /// (配線されていない)ため、本来は常に同じ値(0x0か0xFのどちらか)に
+    /// 揃うはずで、複数の値が混ざっていれば読み出し不良を疑う材料になる。
+    /// 使っていない、またはMBC2以外では空のまま。
+    fn observed_high_nibbles(&self) -> &[u8] {
+        &[]
+    }
+}
+
+/// `--ignore-ram-bank-errors`時、`read_byte`が失敗したら該当バンクにつき
+/// 一度だけ警告を出して`fill_byte`で埋め、呼び出し元がダンプを継続できる
+/// ようにする。一度失敗したバンクの残りのバイトは読まずに`fill_byte`を
+/// 返す。`ignore_bank_errors`が偽の場合は従来通りエラーを伝播する。
+/// このボードにはリトライ機構自体が存在しないため、失敗を検出した最初の
+/// 1回でこのフォールバックへ切り替わる。
+fn read_ram_byte_or_fill(
+    board: &mut CubicStyleBoard,
+    bank: u8,
+    ignore_bank_errors: bool,
+    fill_byte: u8,
+    incomplete_banks: &mut Vec<u8>,
+) -> io::Result<u8> {
+    if ignore_bank_errors && incomplete_banks.contains(&bank) {
+        return Ok(fill_byte);
+    }
+
+    match board.read_byte() {
+        Ok(byte) => Ok(byte),
+        Err(e) if ignore_bank_errors => {
+            if !incomplete_banks.contains(&bank) {
+                eprintln!(
+                    "warning: read failed in RAM bank {} ({:#}); filling the rest of this bank \
+                     with {:#04X} and continuing (--ignore-ram-bank-errors)",
+                    bank, e, fill_byte
+                );
+                incomplete_banks.push(bank);
+            }
+            Ok(fill_byte)
+        }
+        Err(e) => Err(io::Error::new(ErrorKind::BrokenPipe, e)),
+    }
+}
+
+// MBC5+RUMBLEは0x4000 RAMバンクレジスタのビットを1本、モーターの
+// on/offと共用している。標準的な実装はビット3だが、亜種カートリッジでは
+// 異なる場合があるため`--rumble-bit`で上書きできるようにしてある。
+pub const DEFAULT_RUMBLE_BIT: u8 = 3;
+
+/// `new_ram_reader`のフラグ集。[`NewMbcReaderOptions`]と同じ理由で、
+/// 同型の位置引数を並べるのではなくフィールド名付きの構造体を経由する。
+#[derive(Debug, Clone, Default)]
+pub struct NewRamReaderOptions {
+    pub rumble_bit: Option<u8>,
+    pub settle_reads: u32,
+    pub mbc1_mode: u8,
+    pub full_window: bool,
+    pub ignore_bank_errors: bool,
+    pub fill_byte: u8,
+    pub eeprom: bool,
+    pub validate_nibbles: bool,
+    pub nibble_fill: u8,
+}
+
+pub fn new_ram_reader<'a>(
     board: &'a mut CubicStyleBoard,
+    header: &RomHeader,
+    options: NewRamReaderOptions,
+) -> Result<Box<dyn RamReader + 'a>> {
+    let NewRamReaderOptions {
+        rumble_bit,
+        settle_reads,
+        mbc1_mode,
+        full_window,
+        ignore_bank_errors,
+        fill_byte,
+        eeprom,
+        validate_nibbles,
+        nibble_fill,
+    } = options;
+
+    // MBC7の0xA000-0xAFFF窓はSRAMではなく93LC56シリアルEEPROMの
+    // ビットバンギング制御レジスタなので、他のMBCと同じバンク読み出し
+    // ロジックには絶対に流してはいけない。`--eeprom`は明示的なオプトイン
+    // (ヘッダの申告だけで自動的に切り替えると、ヘッダ偽装/破損時に事故る)。
+    if eeprom {
+        return Ok(Box::new(EepromReader::new(board, full_window)));
+    }
 
-    addr: u16,
+    if header.mbc_type == MbcType::Mbc7SensorRumbleRamBattery {
+        bail!(
+            "this cartridge is {} -- it has no SRAM, only a serial EEPROM; pass --eeprom to read it",
+            header.mbc_type
+        );
+    }
+
+    Ok(match header.mbc_type {
+        MbcType::Mbc1Ram | MbcType::Mbc1RamBattery => Box::new(Mbc1RamReader::new(
+            board,
+            header,
+            settle_reads,
+            mbc1_mode,
+            full_window,
+            ignore_bank_errors,
+            fill_byte,
+        )?),
+        MbcType::Mbc2 | MbcType::Mbc2Battery => Box::new(Mbc2RamReader::new(
+            board,
+            full_window,
+            ignore_bank_errors,
+            fill_byte,
+            validate_nibbles,
+            nibble_fill,
+        )),
+        // GAME BOY CAMERAの128KB SRAMは0x4000へ書くバンク値0-15の16バンク
+        // すべてがセーブ/写真データで、`Mbc3RamReader`はこの範囲しか
+        // 書き込まないため、ビット4を立てて選択するカメラのCCD/画像
+        // 処理レジスタ(0xA000-0xA0FF付近)には触れない。
+        MbcType::Mbc3Ram
+        | MbcType::Mbc3RamBattery
+        | MbcType::Mbc3TimerRamBattery
+        | MbcType::PocketCamera => Box::new(Mbc3RamReader::new(
+            board,
+            header,
+            settle_reads,
+            full_window,
+            ignore_bank_errors,
+            fill_byte,
+        )),
+        MbcType::Mbc5Ram | MbcType::Mbc5RamBattery => Box::new(Mbc5RamReader::new(
+            board,
+            header,
+            None,
+            settle_reads,
+            full_window,
+            ignore_bank_errors,
+            fill_byte,
+        )),
+        MbcType::Mbc5RumbleRam | MbcType::Mbc5RumbleRamBattery => Box::new(Mbc5RamReader::new(
+            board,
+            header,
+            Some(rumble_bit.unwrap_or(DEFAULT_RUMBLE_BIT)),
+            settle_reads,
+            full_window,
+            ignore_bank_errors,
+            fill_byte,
+        )),
+        MbcType::Mbc6 => Box::new(Mbc6RamReader::new(
+            board,
+            header,
+            settle_reads,
+            full_window,
+            ignore_bank_errors,
+            fill_byte,
+        )),
+        t => bail!("{} has no RAM to dump", t),
+    })
 }
 
-impl<'a> RomHeaderReader<'a> {
-    pub fn new(board: &'a mut CubicStyleBoard) -> Self {
-        Self { board, addr: 0 }
+/// `new_ram_reader`が実際に読み出しロジックを持つマッパー種別かどうか。
+/// `ReadRam`はボードに触れる(`enable_ram`など)前にこれで早期に弾き、
+/// 素のROM ONLY/MBC1/MBC3/MBC5やROM+RAM、MMM01系のような読み出し未対応の
+/// カートリッジに対して深いところまで進んでからエラーになるのを防ぐ。
+pub fn has_ram_reader(mbc_type: MbcType) -> bool {
+    matches!(
+        mbc_type,
+        MbcType::Mbc1Ram
+            | MbcType::Mbc1RamBattery
+            | MbcType::Mbc2
+            | MbcType::Mbc2Battery
+            | MbcType::Mbc3Ram
+            | MbcType::Mbc3RamBattery
+            | MbcType::Mbc3TimerRamBattery
+            | MbcType::PocketCamera
+            | MbcType::Mbc5Ram
+            | MbcType::Mbc5RamBattery
+            | MbcType::Mbc5RumbleRam
+            | MbcType::Mbc5RumbleRamBattery
+            | MbcType::Mbc6
+    )
+}
+
+/// 宣言されたRAMサイズと`--full-window`の有無から、読み出すべき総バイト数を
+/// 決める。`full_window`が真なら、実際に使われているバンク数分だけ常に
+/// フルの8KBウィンドウを読む(ミラー/未定義領域を含む生のバス挙動を見たい
+/// 場合向け)。
+fn ram_reader_size(header: &RomHeader, full_window: bool) -> usize {
+    if full_window {
+        header.ram_bank_count() * RAM_BANK_SIZE
+    } else {
+        header.ram_size_bytes()
+    }
+}
+
+pub struct Mbc1RamReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u8,
+    settle_reads: u32,
+    ignore_bank_errors: bool,
+    fill_byte: u8,
+    incomplete_banks: Vec<u8>,
+}
+
+// MBC1のバンキングモード(0x6000への書き込み)。モード0(ROMバンキング
+// モード)では0x4000への書き込みはRAMバンクに反映されず、常にバンク0が
+// 選択され続ける -- ここを見落とすと「RAMバンク切り替えが効かない」
+// ように見えるだけで、実際にはモード側の設定漏れであることが多い。
+pub const MBC1_MODE_ROM_BANKING: u8 = 0x00;
+pub const MBC1_MODE_RAM_BANKING: u8 = 0x01;
+
+impl<'a> RamReader for Mbc1RamReader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("RAM BANK#{} {:#04X}", self.bank, self.cur_addr())
+    }
+
+    fn incomplete_banks(&self) -> &[u8] {
+        &self.incomplete_banks
+    }
+}
+
+impl<'a> Mbc1RamReader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: &RomHeader,
+        settle_reads: u32,
+        mode: u8,
+        full_window: bool,
+        ignore_bank_errors: bool,
+        fill_byte: u8,
+    ) -> Result<Self> {
+        board.set_addr(0x6000);
+        board.write_byte(mode)?;
+
+        Ok(Self {
+            board,
+            addr: 0,
+            size: ram_reader_size(header, full_window),
+            bank: 0,
+            settle_reads,
+            ignore_bank_errors,
+            fill_byte,
+            incomplete_banks: Vec::new(),
+        })
+    }
+
+    fn cur_addr(&self) -> u16 {
+        0xA000 + (self.addr % 0x2000) as u16
+    }
+
+    fn select_ram_bank(&mut self) -> Result<()> {
+        self.board.set_addr(0x4000);
+        self.board.write_byte(self.bank)?;
+
+        Ok(())
     }
 
     fn is_valid_addr(&self, addr: i64) -> bool {
-        0 <= addr && addr < 0x150
+        0 <= addr && addr < self.size as i64
     }
 }
 
-impl<'a> Read for RomHeaderReader<'a> {
+impl<'a> Read for Mbc1RamReader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut n = 0;
 
@@ -81,12 +992,23 @@ impl<'a> Read for RomHeaderReader<'a> {
                 break;
             }
 
-            self.board.set_addr(self.addr);
+            if self.addr != 0 && self.addr % 0x2000 == 0 {
+                self.bank += 1;
 
-            *data = self
-                .board
-                .read_byte()
-                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+                self.select_ram_bank()
+                    .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+            }
+
+            self.board.set_addr(self.cur_addr());
+            *data = read_ram_byte_or_fill(
+                self.board,
+                self.bank,
+                self.ignore_bank_errors,
+                self.fill_byte,
+                &mut self.incomplete_banks,
+            )?;
 
             self.addr += 1;
             n += 1;
@@ -96,65 +1018,1382 @@ impl<'a> Read for RomHeaderReader<'a> {
     }
 }
 
-impl<'a> Seek for RomHeaderReader<'a> {
-    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        let addr = match pos {
-            SeekFrom::Start(x) => x as i64,
-            SeekFrom::End(x) => self.addr as i64 + x,
-            SeekFrom::Current(x) => self.addr as i64 + x,
-        };
-
-        if !self.is_valid_addr(addr) {
-            return Err(io::Error::new(ErrorKind::AddrNotAvailable, "out of range"));
-        }
-
-        self.addr = addr as u16;
-
-        Ok(self.addr as u64)
-    }
-}
-
-pub struct RomOnlyReader<'a> {
+pub struct Mbc2RamReader<'a> {
     board: &'a mut CubicStyleBoard,
 
     addr: u16,
+    // `--full-window`時は0x2000(8KBウィンドウ全域)、通常は0x0200
+    // (実際に配線されている512バイト)まで読む。
+    size: u16,
+    ignore_bank_errors: bool,
+    fill_byte: u8,
+    incomplete_banks: Vec<u8>,
+    validate_nibbles: bool,
+    nibble_fill: u8,
+    observed_high_nibbles: Vec<u8>,
 }
 
-impl<'a> MbcReader for RomOnlyReader<'a> {
+impl<'a> RamReader for Mbc2RamReader<'a> {
     fn size(&self) -> usize {
-        0x8000
+        self.size as usize
     }
 
     fn status(&self) -> String {
         format!("{:#04X}", self.addr)
     }
+
+    fn incomplete_banks(&self) -> &[u8] {
+        &self.incomplete_banks
+    }
+
+    fn observed_high_nibbles(&self) -> &[u8] {
+        &self.observed_high_nibbles
+    }
+}
+
+impl<'a> Mbc2RamReader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        full_window: bool,
+        ignore_bank_errors: bool,
+        fill_byte: u8,
+        validate_nibbles: bool,
+        nibble_fill: u8,
+    ) -> Self {
+        let size = if full_window {
+            RAM_BANK_SIZE as u16
+        } else {
+            MBC2_RAM_SIZE as u16
+        };
+
+        Self {
+            board,
+            addr: 0,
+            size,
+            ignore_bank_errors,
+            fill_byte,
+            incomplete_banks: Vec::new(),
+            validate_nibbles,
+            nibble_fill: nibble_fill & 0x0F,
+            observed_high_nibbles: Vec::new(),
+        }
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < self.size as i64
+    }
+}
+
+impl<'a> Read for Mbc2RamReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            self.board.set_addr(0xA000 + self.addr);
+
+            // MBC2は単一の512バイトRAMでバンク切り替え自体を持たないため、
+            // `--ignore-ram-bank-errors`の「バンク」は常に0固定として扱う。
+            let byte = read_ram_byte_or_fill(
+                self.board,
+                0,
+                self.ignore_bank_errors,
+                self.fill_byte,
+                &mut self.incomplete_banks,
+            )?;
+
+            // MBC2の内蔵RAMは下位4bitのみ有効。0x0200を超える領域は
+            // 実チップの外側(ミラー/未定義)なので、`--full-window`時は
+            // 生のバス内容をそのまま残す。
+            *data = if self.addr < MBC2_RAM_SIZE as u16 {
+                if self.validate_nibbles {
+                    let high = byte >> 4;
+
+                    if !self.observed_high_nibbles.contains(&high) {
+                        self.observed_high_nibbles.push(high);
+                    }
+                }
+
+                (self.nibble_fill << 4) | (byte & 0x0F)
+            } else {
+                byte
+            };
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+pub struct Mbc3RamReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u8,
+    settle_reads: u32,
+    ignore_bank_errors: bool,
+    fill_byte: u8,
+    incomplete_banks: Vec<u8>,
+}
+
+impl<'a> RamReader for Mbc3RamReader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("RAM BANK#{} {:#04X}", self.bank, self.cur_addr())
+    }
+
+    fn incomplete_banks(&self) -> &[u8] {
+        &self.incomplete_banks
+    }
+}
+
+impl<'a> Mbc3RamReader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: &RomHeader,
+        settle_reads: u32,
+        full_window: bool,
+        ignore_bank_errors: bool,
+        fill_byte: u8,
+    ) -> Self {
+        Self {
+            board,
+            addr: 0,
+            size: ram_reader_size(header, full_window),
+            bank: 0,
+            settle_reads,
+            ignore_bank_errors,
+            fill_byte,
+            incomplete_banks: Vec::new(),
+        }
+    }
+
+    fn cur_addr(&self) -> u16 {
+        0xA000 + (self.addr % 0x2000) as u16
+    }
+
+    fn select_ram_bank(&mut self) -> Result<()> {
+        self.board.set_addr(0x4000);
+        self.board.write_byte(self.bank)?;
+
+        Ok(())
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < self.size as i64
+    }
+}
+
+impl<'a> Read for Mbc3RamReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            if self.addr != 0 && self.addr % 0x2000 == 0 {
+                self.bank += 1;
+
+                self.select_ram_bank()
+                    .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+            }
+
+            self.board.set_addr(self.cur_addr());
+            *data = read_ram_byte_or_fill(
+                self.board,
+                self.bank,
+                self.ignore_bank_errors,
+                self.fill_byte,
+                &mut self.incomplete_banks,
+            )?;
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+pub struct Mbc5RamReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u8,
+    // RUMBLEバリアントのみ`Some`。0x4000のこのビットは書き込まない。
+    rumble_bit: Option<u8>,
+    settle_reads: u32,
+    ignore_bank_errors: bool,
+    fill_byte: u8,
+    incomplete_banks: Vec<u8>,
+}
+
+impl<'a> RamReader for Mbc5RamReader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("RAM BANK#{} {:#04X}", self.bank, self.cur_addr())
+    }
+
+    fn incomplete_banks(&self) -> &[u8] {
+        &self.incomplete_banks
+    }
+}
+
+impl<'a> Mbc5RamReader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: &RomHeader,
+        rumble_bit: Option<u8>,
+        settle_reads: u32,
+        full_window: bool,
+        ignore_bank_errors: bool,
+        fill_byte: u8,
+    ) -> Self {
+        Self {
+            board,
+            addr: 0,
+            size: ram_reader_size(header, full_window),
+            bank: 0,
+            rumble_bit,
+            settle_reads,
+            ignore_bank_errors,
+            fill_byte,
+            incomplete_banks: Vec::new(),
+        }
+    }
+
+    fn cur_addr(&self) -> u16 {
+        0xA000 + (self.addr % 0x2000) as u16
+    }
+
+    fn select_ram_bank(&mut self) -> Result<()> {
+        if let Some(bit) = self.rumble_bit {
+            if self.bank & (1 << bit) != 0 {
+                bail!(
+                    "RAM bank {} would set bit {} of the 0x4000 register, which this cart \
+                     shares with the rumble motor; refusing to write it. Pass --rumble-bit \
+                     if this cart uses a non-standard bit position",
+                    self.bank,
+                    bit
+                );
+            }
+        }
+
+        self.board.set_addr(0x4000);
+        self.board.write_byte(self.bank)?;
+
+        Ok(())
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < self.size as i64
+    }
+}
+
+impl<'a> Read for Mbc5RamReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            if self.addr != 0 && self.addr % 0x2000 == 0 {
+                self.bank += 1;
+
+                self.select_ram_bank()
+                    .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+            }
+
+            self.board.set_addr(self.cur_addr());
+            *data = read_ram_byte_or_fill(
+                self.board,
+                self.bank,
+                self.ignore_bank_errors,
+                self.fill_byte,
+                &mut self.incomplete_banks,
+            )?;
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+// MBC7(Kirby's Tilt 'n' Tumble/Command Masterのみ)は0xA000-0xAFFF窓に
+// SRAMを持たず、代わりに0xA080に93LC56シリアルEEPROM(128 x 16bitワード
+// = 256バイト)への3線(CS/CLK/DI/DO)ビットバンギング制御レジスタが現れる。
+// ボード側に専用のシリアルEEPROM配線があるわけではなく、既存のパラレル
+// バス(0xA000-0xBFFFウィンドウへの1バイトread/write)だけでゲームボーイ
+// 本体側と同じソフトウェアプロトコルを再現している。
+// @see https://gbdev.io/pandocs/MBC7.html#eeprom-registers-a080-a0ff-read-write
+//
+// このプロトコルは実機での検証はまだ行っていない(公開仕様どおりの
+// ビットバンギングを実装しているのみ)。異なるレイアウトのEEPROM
+// (93C46/93C66など)を積んだ亜種があれば読み出しに失敗しうる。
+const MBC7_EEPROM_ADDR: u16 = 0xA080;
+const MBC7_EEPROM_WORDS: usize = MBC7_EEPROM_SIZE / 2;
+
+const MBC7_CS: u8 = 0b1000_0000;
+const MBC7_CLK: u8 = 0b0100_0000;
+const MBC7_DI: u8 = 0b0000_0010;
+const MBC7_DO: u8 = 0b0000_0001;
+
+// スタートビット(1) + READオペコード(10)。7bitのワードアドレスと
+// 合わせてMSBファーストで10bitのコマンドとしてクロックインする。
+const MBC7_READ_OPCODE: u16 = 0b110;
+
+// MBC6のフラッシュRAM(バックアップ用、0xA000-0xBFFF窓)。バンク選択は
+// 通常のMBCが使う0x4000ではなく0x1000レジスタで行う(ROM窓Bのバンク
+// レジスタ0x3800とは別物)。フラッシュチップは電源投入/リセット直後は
+// 通常「read array」モードで、消去/プログラムのコマンドシーケンスを
+// 送らない限り単純な読み出しとして振る舞う前提で実装している。この
+// 前提及びバンクレジスタの挙動は実機での検証はまだ行っていない。
+pub struct Mbc6RamReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u8,
+    settle_reads: u32,
+    ignore_bank_errors: bool,
+    fill_byte: u8,
+    incomplete_banks: Vec<u8>,
+}
+
+impl<'a> RamReader for Mbc6RamReader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("RAM BANK#{} {:#04X}", self.bank, self.cur_addr())
+    }
+
+    fn incomplete_banks(&self) -> &[u8] {
+        &self.incomplete_banks
+    }
+}
+
+impl<'a> Mbc6RamReader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: &RomHeader,
+        settle_reads: u32,
+        full_window: bool,
+        ignore_bank_errors: bool,
+        fill_byte: u8,
+    ) -> Self {
+        Self {
+            board,
+            addr: 0,
+            size: ram_reader_size(header, full_window),
+            bank: 0,
+            settle_reads,
+            ignore_bank_errors,
+            fill_byte,
+            incomplete_banks: Vec::new(),
+        }
+    }
+
+    fn cur_addr(&self) -> u16 {
+        0xA000 + (self.addr % 0x2000) as u16
+    }
+
+    fn select_ram_bank(&mut self) -> Result<()> {
+        self.board.set_addr(0x1000);
+        self.board.write_byte(self.bank)?;
+
+        Ok(())
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < self.size as i64
+    }
+}
+
+impl<'a> Read for Mbc6RamReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            if self.addr != 0 && self.addr % 0x2000 == 0 {
+                self.bank += 1;
+
+                self.select_ram_bank()
+                    .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+            }
+
+            self.board.set_addr(self.cur_addr());
+            *data = read_ram_byte_or_fill(
+                self.board,
+                self.bank,
+                self.ignore_bank_errors,
+                self.fill_byte,
+                &mut self.incomplete_banks,
+            )?;
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+pub struct EepromReader<'a> {
+    board: &'a mut CubicStyleBoard,
+    addr: usize,
+    size: usize,
+    cached: Option<(usize, u16)>,
+}
+
+impl<'a> RamReader for EepromReader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("EEPROM word {}/{}", self.addr / 2, MBC7_EEPROM_WORDS)
+    }
+}
+
+impl<'a> EepromReader<'a> {
+    pub fn new(board: &'a mut CubicStyleBoard, full_window: bool) -> Self {
+        let size = if full_window {
+            RAM_BANK_SIZE
+        } else {
+            MBC7_EEPROM_SIZE
+        };
+
+        Self {
+            board,
+            addr: 0,
+            size,
+            cached: None,
+        }
+    }
+
+    fn is_valid_addr(&self) -> bool {
+        self.addr < self.size
+    }
+
+    fn clock_bit_out(&mut self, bit: bool) -> Result<()> {
+        let di = if bit { MBC7_DI } else { 0 };
+
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS | di)?;
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS | di | MBC7_CLK)?;
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS | di)?;
+
+        Ok(())
+    }
+
+    fn clock_bit_in(&mut self) -> Result<bool> {
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS)?;
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS | MBC7_CLK)?;
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        let byte = self.board.read_byte()?;
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS)?;
+
+        Ok(byte & MBC7_DO != 0)
+    }
+
+    /// 93LC56のREAD命令を発行し、`word_index`番目(0-127)の16bitワードを
+    /// 読み出す。
+    fn read_word(&mut self, word_index: usize) -> Result<u16> {
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(0)?;
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(MBC7_CS)?;
+
+        let command = (MBC7_READ_OPCODE << 7) | (word_index as u16 & 0x7F);
+
+        for i in (0..10).rev() {
+            self.clock_bit_out((command >> i) & 1 != 0)?;
+        }
+
+        let mut word = 0u16;
+
+        for _ in 0..16 {
+            word = (word << 1) | self.clock_bit_in()? as u16;
+        }
+
+        self.board.set_addr(MBC7_EEPROM_ADDR);
+        self.board.write_byte(0)?;
+
+        Ok(word)
+    }
+}
+
+impl<'a> Read for EepromReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr() {
+                break;
+            }
+
+            let word_index = (self.addr / 2) % MBC7_EEPROM_WORDS;
+
+            let word = match self.cached {
+                Some((cached_word, word)) if cached_word == word_index => word,
+                _ => {
+                    let word = self
+                        .read_word(word_index)
+                        .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+                    self.cached = Some((word_index, word));
+                    word
+                }
+            };
+
+            *data = if self.addr % 2 == 0 {
+                (word >> 8) as u8
+            } else {
+                (word & 0xFF) as u8
+            };
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+pub struct RomHeaderReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u16,
+}
+
+impl<'a> RomHeaderReader<'a> {
+    pub fn new(board: &'a mut CubicStyleBoard) -> Self {
+        Self { board, addr: 0 }
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < 0x150
+    }
+}
+
+impl<'a> Read for RomHeaderReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            self.board.set_addr(self.addr);
+
+            *data = self
+                .board
+                .read_byte()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+impl<'a> Seek for RomHeaderReader<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let addr = match pos {
+            SeekFrom::Start(x) => x as i64,
+            SeekFrom::End(x) => self.addr as i64 + x,
+            SeekFrom::Current(x) => self.addr as i64 + x,
+        };
+
+        if !self.is_valid_addr(addr) {
+            return Err(io::Error::new(ErrorKind::AddrNotAvailable, "out of range"));
+        }
+
+        self.addr = addr as u16;
+
+        Ok(self.addr as u64)
+    }
+}
+
+/// 電源投入後に一度パースした`RomHeader`を保持し、同じカートリッジに
+/// 対して何度も操作を行うライブラリ利用者(REPL的な対話セッション、
+/// ROM読み出しに続けてRAM読み出しを行う場合など)が、そのたびに0x150
+/// バイトの固定領域を読み直さずに済むようにする。このリポジトリの
+/// CLIサブコマンド自体は各々が単発のプロセス実行であり、コマンドを
+/// またいだ状態を持たないため、まだどのサブコマンドからも使われて
+/// いない -- 複数の操作を1つのプロセス内で連続実行する将来の利用者
+/// (このクレートをライブラリとして組み込む側)向けの土台として用意して
+/// ある。
+///
+/// 抜き差し検出はロゴ(0x0104-0x0133、48バイト)とヘッダチェックサム
+/// (0x014D、1バイト)だけを読み直して前回のキャッシュと比較する軽量な
+/// 方法で行う。これはヘッダ全体(0x150バイト)を読み直すよりずっと安く、
+/// 別のカートリッジに差し替わればほぼ確実にどちらかが変化する。
+pub struct HeaderCache {
+    cached: Option<RomHeader>,
+}
+
+impl HeaderCache {
+    pub fn new() -> Self {
+        Self { cached: None }
+    }
+
+    /// キャッシュ済みの`RomHeader`を返す。まだ一度もパースしていない場合、
+    /// またはロゴ/チェックサムの再確認でカートリッジの差し替えを検出した
+    /// 場合は、`refresh_header`と同じ処理で読み直してから返す。
+    pub fn header(&mut self, board: &mut CubicStyleBoard) -> Result<&RomHeader> {
+        let reseated = match &self.cached {
+            Some(cached) => Self::identity_changed(board, cached)?,
+            None => true,
+        };
+
+        if reseated {
+            self.refresh_header(board)?;
+        }
+
+        Ok(self.cached.as_ref().expect("just populated above"))
+    }
+
+    /// キャッシュの有無に関わらず、常にヘッダ領域全体を読み直す。
+    pub fn refresh_header(&mut self, board: &mut CubicStyleBoard) -> Result<&RomHeader> {
+        let mut reader = RomHeaderReader::new(board);
+        let header = RomHeader::from_reader(&mut reader)?;
+
+        self.cached = Some(header);
+
+        Ok(self.cached.as_ref().expect("just populated above"))
+    }
+
+    /// 明示的にキャッシュを破棄する。次回の`header()`呼び出しで必ず
+    /// 読み直す。
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    fn identity_changed(board: &mut CubicStyleBoard, cached: &RomHeader) -> Result<bool> {
+        let mut logo = [0u8; 0x0030];
+
+        for (i, byte) in logo.iter_mut().enumerate() {
+            board.set_addr(0x0104 + i as u16);
+            *byte = board.read_byte()?;
+        }
+
+        board.set_addr(0x014D);
+        let header_checksum = board.read_byte()?;
+
+        Ok(logo != cached.logo || header_checksum != cached.header_checksum)
+    }
+}
+
+impl Default for HeaderCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct RomOnlyReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u16,
+    keep_going: bool,
+    faults: Vec<u32>,
+}
+
+impl<'a> MbcReader for RomOnlyReader<'a> {
+    fn size(&self) -> usize {
+        0x8000
+    }
+
+    fn status(&self) -> String {
+        format!("{:#04X}", self.addr)
+    }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+
+    fn seek_to(&mut self, bank: u16, addr: u16) -> Result<()> {
+        if bank != 0 {
+            bail!("seek_to: this cartridge has no switchable ROM banks (ROM ONLY), bank must be 0");
+        }
+
+        if !self.is_valid_addr(addr as i64) {
+            bail!("seek_to: addr {:#06X} is out of range for a {}-byte ROM", addr, self.size());
+        }
+
+        self.addr = addr;
+
+        Ok(())
+    }
+}
+
+impl<'a> RomOnlyReader<'a> {
+    pub fn new(board: &'a mut CubicStyleBoard, _header: RomHeader, keep_going: bool) -> Self {
+        Self {
+            board,
+            addr: 0,
+            keep_going,
+            faults: Vec::new(),
+        }
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < 0x8000
+    }
+}
+
+impl<'a> Read for RomOnlyReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            self.board.set_addr(self.addr);
+
+            *data = read_byte_or_fill(
+                self.board,
+                self.addr as u32,
+                self.keep_going,
+                &mut self.faults,
+            )?;
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+// カートリッジタイプバイトがどの既知コードとも一致しない場合の保守的な
+// フォールバック。実際のバンキング方式が分からないため、MBC1形式
+// (0x2000下位5bit+0x4000上位2bit)とMBC5形式(0x2000下位8bit+0x3000
+// 上位1bit)の両方へバンク番号を投機的に書き込んでおく -- 見当違いの
+// レジスタへの書き込みは単に無視されるだけで実害はなく、実機がどちらか
+// に反応すればバンク1以降からも正しいデータが拾える可能性がある。ROM
+// サイズはヘッダの申告値を使うが、それ自体が信頼できない前提のカートの
+// ため、申告が無い(0バイト)場合はバンク切り替え無しの最小構成である
+// 32KBにフォールバックする。
+pub struct UnknownMbcReader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u16,
+    cartridge_type_byte: u8,
+    keep_going: bool,
+    faults: Vec<u32>,
+}
+
+impl<'a> MbcReader for UnknownMbcReader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "UNKNOWN(0x{:02X}) BANK#{} {:#04X}",
+            self.cartridge_type_byte,
+            self.bank,
+            self.cur_addr()
+        )
+    }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+}
+
+impl<'a> UnknownMbcReader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: RomHeader,
+        cartridge_type_byte: u8,
+        keep_going: bool,
+    ) -> Self {
+        Self {
+            board,
+            addr: 0,
+            size: header.rom_size.max(0x8000),
+            bank: 0,
+            cartridge_type_byte,
+            keep_going,
+            faults: Vec::new(),
+        }
+    }
+
+    fn cur_addr(&self) -> u16 {
+        (if self.addr >= 0x4000 {
+            self.addr % 0x4000 + 0x4000
+        } else {
+            self.addr
+        }) as u16
+    }
+
+    /// 書き込み先が的外れでも実害が無いため、失敗は無視する。
+    fn best_effort_select_bank(&mut self) {
+        let bank_mbc1_low = (self.bank & 0b0001_1111) as u8;
+        let bank_mbc1_high = ((self.bank >> 5) & 0b0000_0011) as u8;
+        let bank_mbc5_low = (self.bank & 0xFF) as u8;
+        let bank_mbc5_high = ((self.bank >> 8) & 0b0000_0001) as u8;
+
+        self.board.set_addr(0x2000);
+        let _ = self.board.write_byte(bank_mbc1_low);
+        self.board.set_addr(0x4000);
+        let _ = self.board.write_byte(bank_mbc1_high);
+
+        self.board.set_addr(0x2000);
+        let _ = self.board.write_byte(bank_mbc5_low);
+        self.board.set_addr(0x3000);
+        let _ = self.board.write_byte(bank_mbc5_high);
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < self.size as i64
+    }
 }
 
-impl<'a> RomOnlyReader<'a> {
-    pub fn new(board: &'a mut CubicStyleBoard, _header: RomHeader) -> Self {
-        Self { board, addr: 0 }
+impl<'a> Read for UnknownMbcReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            if self.addr != 0 && self.addr % 0x4000 == 0 {
+                self.bank += 1;
+                self.best_effort_select_bank();
+            }
+
+            self.board.set_addr(self.cur_addr());
+            *data = read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?;
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+pub struct Mbc1Reader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u8,
+    settle_reads: u32,
+    bank_select_strategy: BankSelectStrategy,
+    keep_going: bool,
+    faults: Vec<u32>,
+    verify_bank_switch: bool,
+    last_bank_switch_sample: Option<u8>,
+    bank_switch_faults: Vec<u8>,
+    retry_whole_bank: Option<u32>,
+    unstable_banks: Vec<u8>,
+    bank_cache: Option<Vec<u8>>,
+}
+
+impl<'a> MbcReader for Mbc1Reader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("BANK#{} {:#04X}", self.bank, self.cur_addr())
+    }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+
+    fn bank_switch_faults(&self) -> &[u8] {
+        &self.bank_switch_faults
+    }
+
+    fn unstable_banks(&self) -> &[u8] {
+        &self.unstable_banks
+    }
+
+    fn seek_to(&mut self, bank: u16, addr: u16) -> Result<()> {
+        if addr >= 0x4000 {
+            bail!("seek_to: addr must be within a single bank window (0x0000-0x3FFF), got {:#06X}", addr);
+        }
+
+        let global = bank as u32 * 0x4000 + addr as u32;
+
+        if global as usize >= self.size {
+            bail!(
+                "seek_to: bank {} addr {:#06X} is out of range for a {}-byte ROM",
+                bank, addr, self.size
+            );
+        }
+
+        self.addr = global;
+        self.bank = bank as u8;
+
+        if bank != 0 {
+            self.select_rom_bank()?;
+            settle(self.board, self.cur_addr(), self.settle_reads)?;
+            self.maybe_verify_bank_switch()?;
+            self.bank_cache = self.maybe_retry_whole_bank()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Mbc1Reader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: RomHeader,
+        settle_reads: u32,
+        bank_select_strategy: BankSelectStrategy,
+        keep_going: bool,
+        verify_bank_switch: bool,
+        retry_whole_bank: Option<u32>,
+    ) -> Self {
+        Self {
+            board,
+            addr: 0,
+            size: header.rom_size,
+            bank: 0,
+            settle_reads,
+            bank_select_strategy,
+            keep_going,
+            faults: Vec::new(),
+            verify_bank_switch,
+            last_bank_switch_sample: None,
+            bank_switch_faults: Vec::new(),
+            retry_whole_bank,
+            unstable_banks: Vec::new(),
+            bank_cache: None,
+        }
+    }
+
+    fn maybe_verify_bank_switch(&mut self) -> io::Result<()> {
+        if self.verify_bank_switch {
+            verify_bank_switch(
+                self.board,
+                self.bank,
+                &mut self.last_bank_switch_sample,
+                &mut self.bank_switch_faults,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// `--retry-whole-bank`が有効な場合、現在の`self.bank`の切り替え可能
+    /// ウィンドウ(0x4000バイト)を丸ごと読み、2回連続で同じ内容になるまで
+    /// バンクを再選択して読み直す。失敗したバイトを0xFFで埋めるだけの
+    /// `--keep-going`と違い、バンク切り替えレジスタの状態自体が乱れている
+    /// 可能性に対応するため、切り替えからやり直す点が異なる。無効なら`None`を返し、以降の
+    /// `read()`は従来通りハードウェアから直接1バイトずつ読む。
+    fn maybe_retry_whole_bank(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let max_retries = match self.retry_whole_bank {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let base = self.cur_addr();
+        let mut previous = read_bank_window(self.board, base, 0x4000)?;
+
+        for attempt in 0..max_retries {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            settle(self.board, base, self.settle_reads)?;
+
+            let current = read_bank_window(self.board, base, 0x4000)?;
+
+            if current == previous {
+                return Ok(Some(current));
+            }
+
+            eprintln!(
+                "warning: bank {} was unstable across whole-bank re-reads (attempt {}/{}); \
+                 re-selecting and reading the whole bank again",
+                self.bank, attempt + 1, max_retries
+            );
+
+            if !self.unstable_banks.contains(&self.bank) {
+                self.unstable_banks.push(self.bank);
+            }
+
+            previous = current;
+        }
+
+        eprintln!(
+            "warning: bank {} did not stabilize after {} whole-bank re-reads; using the last read",
+            self.bank, max_retries
+        );
+
+        Ok(Some(previous))
+    }
+
+    // バンク0x20/0x40/0x60はスイッチャブル窓(0x2000へのゼロ書き込みは
+    // 常に+1される既知のMBC1のクセ)からは選べない。モード1にして
+    // セカンダリレジスタ(0x4000)にその上位2ビットを書くと、固定領域
+    // (0x0000-0x3FFF)側がそのバンクへ丸ごと差し替わるので、512KBを
+    // 超えるカートではこの窓を借りてその3バンクだけ読む。
+    fn is_fixed_region_quirk_bank(&self) -> bool {
+        matches!(self.bank, 0x20 | 0x40 | 0x60)
+    }
+
+    fn cur_addr(&self) -> u16 {
+        if self.addr < 0x4000 {
+            return self.addr as u16;
+        }
+
+        let offset = (self.addr % 0x4000) as u16;
+
+        if self.is_fixed_region_quirk_bank() {
+            offset
+        } else {
+            offset + 0x4000
+        }
+    }
+
+    fn select_rom_bank(&mut self) -> Result<()> {
+        if self.is_fixed_region_quirk_bank() {
+            self.board.set_addr(0x6000);
+            self.board.write_byte(1)?; // mode 1: RAM banking / advanced ROM banking
+
+            self.board.set_addr(0x4000);
+            self.board.write_byte((self.bank >> 5) & 0b00000011)?;
+        } else {
+            self.board.set_addr(0x6000);
+            self.board.write_byte(0)?; // mode 0: ROM banking
+
+            let bank_low = self.bank & 0b00011111;
+            let bank_high = (self.bank >> 5) & 0b00000011;
+
+            self.board.set_addr(0x2000);
+            self.board.write_byte(bank_low)?;
+
+            self.board.set_addr(0x4000);
+            self.board.write_byte(bank_high)?;
+        }
+
+        Ok(())
     }
 
     fn is_valid_addr(&self, addr: i64) -> bool {
-        0 <= addr && addr < 0x8000
+        0 <= addr && addr < self.size as i64
+    }
+}
+
+impl<'a> Read for Mbc1Reader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+
+        if self.bank_select_strategy == BankSelectStrategy::PerChunk
+            && self.is_valid_addr(self.addr as i64)
+        {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+        }
+
+        for data in buf.iter_mut() {
+            if !self.is_valid_addr(self.addr as i64) {
+                break;
+            }
+
+            if self.addr != 0 && self.addr % 0x4000 == 0 {
+                self.bank += 1;
+
+                self.select_rom_bank()
+                    .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+                self.maybe_verify_bank_switch()?;
+                self.bank_cache = self.maybe_retry_whole_bank()?;
+            }
+
+            *data = match &self.bank_cache {
+                Some(cache) => cache[(self.addr % 0x4000) as usize],
+                None => {
+                    self.board.set_addr(self.cur_addr());
+                    read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?
+                }
+            };
+
+            self.addr += 1;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+pub struct Mbc2Reader<'a> {
+    board: &'a mut CubicStyleBoard,
+
+    addr: u32,
+    size: usize,
+    bank: u8,
+    settle_reads: u32,
+    bank_select_strategy: BankSelectStrategy,
+    keep_going: bool,
+    faults: Vec<u32>,
+    verify_bank_switch: bool,
+    last_bank_switch_sample: Option<u8>,
+    bank_switch_faults: Vec<u8>,
+    retry_whole_bank: Option<u32>,
+    unstable_banks: Vec<u8>,
+    bank_cache: Option<Vec<u8>>,
+}
+
+impl<'a> MbcReader for Mbc2Reader<'a> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn status(&self) -> String {
+        format!("BANK#{} {:#04X}", self.bank, self.cur_addr())
+    }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+
+    fn bank_switch_faults(&self) -> &[u8] {
+        &self.bank_switch_faults
+    }
+
+    fn unstable_banks(&self) -> &[u8] {
+        &self.unstable_banks
+    }
+
+    fn seek_to(&mut self, bank: u16, addr: u16) -> Result<()> {
+        if addr >= 0x4000 {
+            bail!("seek_to: addr must be within a single bank window (0x0000-0x3FFF), got {:#06X}", addr);
+        }
+
+        if bank > 0x0F {
+            bail!("seek_to: MBC2 only has 16 ROM banks (0-15), got bank {}", bank);
+        }
+
+        let global = bank as u32 * 0x4000 + addr as u32;
+
+        if global as usize >= self.size {
+            bail!(
+                "seek_to: bank {} addr {:#06X} is out of range for a {}-byte ROM",
+                bank, addr, self.size
+            );
+        }
+
+        self.addr = global;
+        self.bank = bank as u8;
+
+        if bank != 0 {
+            self.select_rom_bank()?;
+            settle(self.board, self.cur_addr(), self.settle_reads)?;
+            self.maybe_verify_bank_switch()?;
+            self.bank_cache = self.maybe_retry_whole_bank()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Mbc2Reader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: RomHeader,
+        settle_reads: u32,
+        bank_select_strategy: BankSelectStrategy,
+        keep_going: bool,
+        verify_bank_switch: bool,
+        retry_whole_bank: Option<u32>,
+    ) -> Self {
+        Self {
+            board,
+            addr: 0,
+            size: header.rom_size,
+            bank: 0,
+            settle_reads,
+            bank_select_strategy,
+            keep_going,
+            faults: Vec::new(),
+            verify_bank_switch,
+            last_bank_switch_sample: None,
+            bank_switch_faults: Vec::new(),
+            retry_whole_bank,
+            unstable_banks: Vec::new(),
+            bank_cache: None,
+        }
+    }
+
+    fn cur_addr(&self) -> u16 {
+        (if self.addr >= 0x4000 {
+            self.addr % 0x4000 + 0x4000
+        } else {
+            self.addr
+        }) as u16
+    }
+
+    fn select_rom_bank(&mut self) -> Result<()> {
+        let bank = self.bank & 0b00001111;
+
+        self.board.set_addr(0x2100);
+        self.board.write_byte(bank)?;
+
+        Ok(())
+    }
+
+    fn is_valid_addr(&self, addr: i64) -> bool {
+        0 <= addr && addr < self.size as i64
+    }
+
+    fn maybe_verify_bank_switch(&mut self) -> io::Result<()> {
+        if self.verify_bank_switch {
+            verify_bank_switch(
+                self.board,
+                self.bank,
+                &mut self.last_bank_switch_sample,
+                &mut self.bank_switch_faults,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn maybe_retry_whole_bank(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let max_retries = match self.retry_whole_bank {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let base = self.cur_addr();
+        let mut previous = read_bank_window(self.board, base, 0x4000)?;
+
+        for attempt in 0..max_retries {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            settle(self.board, base, self.settle_reads)?;
+
+            let current = read_bank_window(self.board, base, 0x4000)?;
+
+            if current == previous {
+                return Ok(Some(current));
+            }
+
+            eprintln!(
+                "warning: bank {} was unstable across whole-bank re-reads (attempt {}/{}); \
+                 re-selecting and reading the whole bank again",
+                self.bank, attempt + 1, max_retries
+            );
+
+            if !self.unstable_banks.contains(&self.bank) {
+                self.unstable_banks.push(self.bank);
+            }
+
+            previous = current;
+        }
+
+        eprintln!(
+            "warning: bank {} did not stabilize after {} whole-bank re-reads; using the last read",
+            self.bank, max_retries
+        );
+
+        Ok(Some(previous))
     }
 }
 
-impl<'a> Read for RomOnlyReader<'a> {
+impl<'a> Read for Mbc2Reader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut n = 0;
 
+        if self.bank_select_strategy == BankSelectStrategy::PerChunk
+            && self.is_valid_addr(self.addr as i64)
+        {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+        }
+
         for data in buf.iter_mut() {
             if !self.is_valid_addr(self.addr as i64) {
                 break;
             }
 
-            self.board.set_addr(self.addr);
+            if self.addr != 0 && self.addr % 0x4000 == 0 {
+                self.bank += 1;
 
-            *data = self
-                .board
-                .read_byte()
-                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+                self.select_rom_bank()
+                    .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+                self.maybe_verify_bank_switch()?;
+                self.bank_cache = self.maybe_retry_whole_bank()?;
+            }
+
+            *data = match &self.bank_cache {
+                Some(cache) => cache[(self.addr % 0x4000) as usize],
+                None => {
+                    self.board.set_addr(self.cur_addr());
+                    read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?
+                }
+            };
 
             self.addr += 1;
             n += 1;
@@ -164,15 +2403,25 @@ impl<'a> Read for RomOnlyReader<'a> {
     }
 }
 
-pub struct Mbc1Reader<'a> {
+pub struct Mbc3Reader<'a> {
     board: &'a mut CubicStyleBoard,
 
     addr: u32,
     size: usize,
     bank: u8,
+    settle_reads: u32,
+    bank_select_strategy: BankSelectStrategy,
+    keep_going: bool,
+    faults: Vec<u32>,
+    verify_bank_switch: bool,
+    last_bank_switch_sample: Option<u8>,
+    bank_switch_faults: Vec<u8>,
+    retry_whole_bank: Option<u32>,
+    unstable_banks: Vec<u8>,
+    bank_cache: Option<Vec<u8>>,
 }
 
-impl<'a> MbcReader for Mbc1Reader<'a> {
+impl<'a> MbcReader for Mbc3Reader<'a> {
     fn size(&self) -> usize {
         self.size
     }
@@ -180,15 +2429,80 @@ impl<'a> MbcReader for Mbc1Reader<'a> {
     fn status(&self) -> String {
         format!("BANK#{} {:#04X}", self.bank, self.cur_addr())
     }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+
+    fn bank_switch_faults(&self) -> &[u8] {
+        &self.bank_switch_faults
+    }
+
+    fn unstable_banks(&self) -> &[u8] {
+        &self.unstable_banks
+    }
+
+    fn seek_to(&mut self, bank: u16, addr: u16) -> Result<()> {
+        if addr >= 0x4000 {
+            bail!("seek_to: addr must be within a single bank window (0x0000-0x3FFF), got {:#06X}", addr);
+        }
+
+        if bank > 0x7F {
+            bail!("seek_to: MBC3's ROM bank register is 7 bits wide (0-127), got bank {}", bank);
+        }
+
+        let global = bank as u32 * 0x4000 + addr as u32;
+
+        if global as usize >= self.size {
+            bail!(
+                "seek_to: bank {} addr {:#06X} is out of range for a {}-byte ROM",
+                bank, addr, self.size
+            );
+        }
+
+        self.addr = global;
+        self.bank = bank as u8;
+
+        if bank != 0 {
+            self.select_rom_bank()?;
+            settle(self.board, self.cur_addr(), self.settle_reads)?;
+            self.maybe_verify_bank_switch()?;
+            self.bank_cache = self.maybe_retry_whole_bank()?;
+        }
+
+        Ok(())
+    }
 }
 
-impl<'a> Mbc1Reader<'a> {
-    pub fn new(board: &'a mut CubicStyleBoard, header: RomHeader) -> Self {
+impl<'a> Mbc3Reader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: RomHeader,
+        settle_reads: u32,
+        bank_select_strategy: BankSelectStrategy,
+        keep_going: bool,
+        verify_bank_switch: bool,
+        retry_whole_bank: Option<u32>,
+    ) -> Self {
         Self {
             board,
             addr: 0,
             size: header.rom_size,
             bank: 0,
+            settle_reads,
+            bank_select_strategy,
+            keep_going,
+            faults: Vec::new(),
+            verify_bank_switch,
+            last_bank_switch_sample: None,
+            bank_switch_faults: Vec::new(),
+            retry_whole_bank,
+            unstable_banks: Vec::new(),
+            bank_cache: None,
         }
     }
 
@@ -201,14 +2515,8 @@ impl<'a> Mbc1Reader<'a> {
     }
 
     fn select_rom_bank(&mut self) -> Result<()> {
-        let bank_low = self.bank & 0b00011111;
-        let bank_high = (self.bank >> 5) & 0b00000011;
-
         self.board.set_addr(0x2000);
-        self.board.write_byte(bank_low)?;
-
-        self.board.set_addr(0x4000);
-        self.board.write_byte(bank_high)?;
+        self.board.write_byte(self.bank)?;
 
         Ok(())
     }
@@ -216,12 +2524,73 @@ impl<'a> Mbc1Reader<'a> {
     fn is_valid_addr(&self, addr: i64) -> bool {
         0 <= addr && addr < self.size as i64
     }
+
+    fn maybe_verify_bank_switch(&mut self) -> io::Result<()> {
+        if self.verify_bank_switch {
+            verify_bank_switch(
+                self.board,
+                self.bank,
+                &mut self.last_bank_switch_sample,
+                &mut self.bank_switch_faults,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn maybe_retry_whole_bank(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let max_retries = match self.retry_whole_bank {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let base = self.cur_addr();
+        let mut previous = read_bank_window(self.board, base, 0x4000)?;
+
+        for attempt in 0..max_retries {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            settle(self.board, base, self.settle_reads)?;
+
+            let current = read_bank_window(self.board, base, 0x4000)?;
+
+            if current == previous {
+                return Ok(Some(current));
+            }
+
+            eprintln!(
+                "warning: bank {} was unstable across whole-bank re-reads (attempt {}/{}); \
+                 re-selecting and reading the whole bank again",
+                self.bank, attempt + 1, max_retries
+            );
+
+            if !self.unstable_banks.contains(&self.bank) {
+                self.unstable_banks.push(self.bank);
+            }
+
+            previous = current;
+        }
+
+        eprintln!(
+            "warning: bank {} did not stabilize after {} whole-bank re-reads; using the last read",
+            self.bank, max_retries
+        );
+
+        Ok(Some(previous))
+    }
 }
 
-impl<'a> Read for Mbc1Reader<'a> {
+impl<'a> Read for Mbc3Reader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut n = 0;
 
+        if self.bank_select_strategy == BankSelectStrategy::PerChunk
+            && self.is_valid_addr(self.addr as i64)
+        {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+        }
+
         for data in buf.iter_mut() {
             if !self.is_valid_addr(self.addr as i64) {
                 break;
@@ -230,22 +2599,21 @@ impl<'a> Read for Mbc1Reader<'a> {
             if self.addr != 0 && self.addr % 0x4000 == 0 {
                 self.bank += 1;
 
-                match self.bank {
-                    0x20 | 0x40 | 0x60 => {
-                        self.bank += 1;
-                    }
-                    _ => {}
-                }
-
                 self.select_rom_bank()
                     .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+                self.maybe_verify_bank_switch()?;
+                self.bank_cache = self.maybe_retry_whole_bank()?;
             }
 
-            self.board.set_addr(self.cur_addr());
-            *data = self
-                .board
-                .read_byte()
-                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            *data = match &self.bank_cache {
+                Some(cache) => cache[(self.addr % 0x4000) as usize],
+                None => {
+                    self.board.set_addr(self.cur_addr());
+                    read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?
+                }
+            };
 
             self.addr += 1;
             n += 1;
@@ -255,15 +2623,25 @@ impl<'a> Read for Mbc1Reader<'a> {
     }
 }
 
-pub struct Mbc2Reader<'a> {
+pub struct Mbc5Reader<'a> {
     board: &'a mut CubicStyleBoard,
 
     addr: u32,
     size: usize,
-    bank: u8,
+    bank: u16,
+    settle_reads: u32,
+    bank_select_strategy: BankSelectStrategy,
+    keep_going: bool,
+    faults: Vec<u32>,
+    verify_bank_switch: bool,
+    last_bank_switch_sample: Option<u8>,
+    bank_switch_faults: Vec<u8>,
+    retry_whole_bank: Option<u32>,
+    unstable_banks: Vec<u8>,
+    bank_cache: Option<Vec<u8>>,
 }
 
-impl<'a> MbcReader for Mbc2Reader<'a> {
+impl<'a> MbcReader for Mbc5Reader<'a> {
     fn size(&self) -> usize {
         self.size
     }
@@ -271,15 +2649,80 @@ impl<'a> MbcReader for Mbc2Reader<'a> {
     fn status(&self) -> String {
         format!("BANK#{} {:#04X}", self.bank, self.cur_addr())
     }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+
+    fn bank_switch_faults(&self) -> &[u8] {
+        &self.bank_switch_faults
+    }
+
+    fn unstable_banks(&self) -> &[u8] {
+        &self.unstable_banks
+    }
+
+    fn seek_to(&mut self, bank: u16, addr: u16) -> Result<()> {
+        if addr >= 0x4000 {
+            bail!("seek_to: addr must be within a single bank window (0x0000-0x3FFF), got {:#06X}", addr);
+        }
+
+        if bank > 0x01FF {
+            bail!("seek_to: MBC5's ROM bank register is 9 bits wide (0-511), got bank {}", bank);
+        }
+
+        let global = bank as u32 * 0x4000 + addr as u32;
+
+        if global as usize >= self.size {
+            bail!(
+                "seek_to: bank {} addr {:#06X} is out of range for a {}-byte ROM",
+                bank, addr, self.size
+            );
+        }
+
+        self.addr = global;
+        self.bank = bank;
+
+        if bank != 0 {
+            self.select_rom_bank()?;
+            settle(self.board, self.cur_addr(), self.settle_reads)?;
+            self.maybe_verify_bank_switch()?;
+            self.bank_cache = self.maybe_retry_whole_bank()?;
+        }
+
+        Ok(())
+    }
 }
 
-impl<'a> Mbc2Reader<'a> {
-    pub fn new(board: &'a mut CubicStyleBoard, header: RomHeader) -> Self {
+impl<'a> Mbc5Reader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: RomHeader,
+        settle_reads: u32,
+        bank_select_strategy: BankSelectStrategy,
+        keep_going: bool,
+        verify_bank_switch: bool,
+        retry_whole_bank: Option<u32>,
+    ) -> Self {
         Self {
             board,
             addr: 0,
             size: header.rom_size,
             bank: 0,
+            settle_reads,
+            bank_select_strategy,
+            keep_going,
+            faults: Vec::new(),
+            verify_bank_switch,
+            last_bank_switch_sample: None,
+            bank_switch_faults: Vec::new(),
+            retry_whole_bank,
+            unstable_banks: Vec::new(),
+            bank_cache: None,
         }
     }
 
@@ -292,10 +2735,14 @@ impl<'a> Mbc2Reader<'a> {
     }
 
     fn select_rom_bank(&mut self) -> Result<()> {
-        let bank = self.bank & 0b00001111;
+        let bank_low = (self.bank & 0xFF) as u8;
+        let bank_high = ((self.bank >> 8) & 0b00000001) as u8;
 
-        self.board.set_addr(0x2100);
-        self.board.write_byte(bank)?;
+        self.board.set_addr(0x2000);
+        self.board.write_byte(bank_low)?;
+
+        self.board.set_addr(0x3000);
+        self.board.write_byte(bank_high)?;
 
         Ok(())
     }
@@ -303,12 +2750,75 @@ impl<'a> Mbc2Reader<'a> {
     fn is_valid_addr(&self, addr: i64) -> bool {
         0 <= addr && addr < self.size as i64
     }
+
+    fn maybe_verify_bank_switch(&mut self) -> io::Result<()> {
+        if self.verify_bank_switch {
+            verify_bank_switch(
+                self.board,
+                (self.bank & 0xFF) as u8,
+                &mut self.last_bank_switch_sample,
+                &mut self.bank_switch_faults,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn maybe_retry_whole_bank(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let max_retries = match self.retry_whole_bank {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let base = self.cur_addr();
+        let mut previous = read_bank_window(self.board, base, 0x4000)?;
+
+        for attempt in 0..max_retries {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            settle(self.board, base, self.settle_reads)?;
+
+            let current = read_bank_window(self.board, base, 0x4000)?;
+
+            if current == previous {
+                return Ok(Some(current));
+            }
+
+            let bank = (self.bank & 0xFF) as u8;
+
+            eprintln!(
+                "warning: bank {} was unstable across whole-bank re-reads (attempt {}/{}); \
+                 re-selecting and reading the whole bank again",
+                self.bank, attempt + 1, max_retries
+            );
+
+            if !self.unstable_banks.contains(&bank) {
+                self.unstable_banks.push(bank);
+            }
+
+            previous = current;
+        }
+
+        eprintln!(
+            "warning: bank {} did not stabilize after {} whole-bank re-reads; using the last read",
+            self.bank, max_retries
+        );
+
+        Ok(Some(previous))
+    }
 }
 
-impl<'a> Read for Mbc2Reader<'a> {
+impl<'a> Read for Mbc5Reader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut n = 0;
 
+        if self.bank_select_strategy == BankSelectStrategy::PerChunk
+            && self.is_valid_addr(self.addr as i64)
+        {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+        }
+
         for data in buf.iter_mut() {
             if !self.is_valid_addr(self.addr as i64) {
                 break;
@@ -319,13 +2829,19 @@ impl<'a> Read for Mbc2Reader<'a> {
 
                 self.select_rom_bank()
                     .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+                self.maybe_verify_bank_switch()?;
+                self.bank_cache = self.maybe_retry_whole_bank()?;
             }
 
-            self.board.set_addr(self.cur_addr());
-            *data = self
-                .board
-                .read_byte()
-                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            *data = match &self.bank_cache {
+                Some(cache) => cache[(self.addr % 0x4000) as usize],
+                None => {
+                    self.board.set_addr(self.cur_addr());
+                    read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?
+                }
+            };
 
             self.addr += 1;
             n += 1;
@@ -335,15 +2851,38 @@ impl<'a> Read for Mbc2Reader<'a> {
     }
 }
 
-pub struct Mbc3Reader<'a> {
+// MBC6(「がんばれゴエモン ネオモモタロウ電鉄でGO」専用)。他のMBCと
+// 異なり、切り替え可能な2つの8KB窓を独立したレジスタで持つ:
+//   0x2000 - ROM Bank番号(窓A、0x4000-0x5FFF)
+//   0x2800 - RAMバンク番号(窓A側、フラッシュ用、ここでは未使用)
+//   0x3000 - ROM Bank番号(窓B、0x6000-0x7FFF)
+//   0x3800 - RAMバンク番号(窓B側、フラッシュ用、ここでは未使用)
+// 窓Aだけで全バンク(8KB単位)へアクセスできるため、連続ダンプでは窓Bには
+// 一切触れない。バンク番号はROM先頭からの8KBオフセットにそのまま対応する
+// 前提(固定領域の2バンク分を含む)で実装しているが、実機での検証は
+// このリポジトリ内では行っていない。書き込み(Bank切り替え以外の、
+// フラッシュの消去/プログラムコマンドシーケンス)は未対応で今後の課題。
+const MBC6_BANK_SIZE: u32 = 0x2000;
+
+pub struct Mbc6Reader<'a> {
     board: &'a mut CubicStyleBoard,
 
     addr: u32,
     size: usize,
-    bank: u8,
+    bank: u16,
+    settle_reads: u32,
+    bank_select_strategy: BankSelectStrategy,
+    keep_going: bool,
+    faults: Vec<u32>,
+    verify_bank_switch: bool,
+    last_bank_switch_sample: Option<u8>,
+    bank_switch_faults: Vec<u8>,
+    retry_whole_bank: Option<u32>,
+    unstable_banks: Vec<u8>,
+    bank_cache: Option<Vec<u8>>,
 }
 
-impl<'a> MbcReader for Mbc3Reader<'a> {
+impl<'a> MbcReader for Mbc6Reader<'a> {
     fn size(&self) -> usize {
         self.size
     }
@@ -351,29 +2890,63 @@ impl<'a> MbcReader for Mbc3Reader<'a> {
     fn status(&self) -> String {
         format!("BANK#{} {:#04X}", self.bank, self.cur_addr())
     }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
+
+    fn bank_switch_faults(&self) -> &[u8] {
+        &self.bank_switch_faults
+    }
+
+    fn unstable_banks(&self) -> &[u8] {
+        &self.unstable_banks
+    }
 }
 
-impl<'a> Mbc3Reader<'a> {
-    pub fn new(board: &'a mut CubicStyleBoard, header: RomHeader) -> Self {
+impl<'a> Mbc6Reader<'a> {
+    pub fn new(
+        board: &'a mut CubicStyleBoard,
+        header: RomHeader,
+        settle_reads: u32,
+        bank_select_strategy: BankSelectStrategy,
+        keep_going: bool,
+        verify_bank_switch: bool,
+        retry_whole_bank: Option<u32>,
+    ) -> Self {
         Self {
             board,
             addr: 0,
             size: header.rom_size,
             bank: 0,
+            settle_reads,
+            bank_select_strategy,
+            keep_going,
+            faults: Vec::new(),
+            verify_bank_switch,
+            last_bank_switch_sample: None,
+            bank_switch_faults: Vec::new(),
+            retry_whole_bank,
+            unstable_banks: Vec::new(),
+            bank_cache: None,
         }
     }
 
     fn cur_addr(&self) -> u16 {
-        (if self.addr >= 0x4000 {
-            self.addr % 0x4000 + 0x4000
+        if self.addr < 0x4000 {
+            self.addr as u16
         } else {
-            self.addr
-        }) as u16
+            0x4000 + (self.addr % MBC6_BANK_SIZE) as u16
+        }
     }
 
     fn select_rom_bank(&mut self) -> Result<()> {
         self.board.set_addr(0x2000);
-        self.board.write_byte(self.bank)?;
+        self.board.write_byte((self.bank & 0xFF) as u8)?;
 
         Ok(())
     }
@@ -381,29 +2954,98 @@ impl<'a> Mbc3Reader<'a> {
     fn is_valid_addr(&self, addr: i64) -> bool {
         0 <= addr && addr < self.size as i64
     }
+
+    fn maybe_verify_bank_switch(&mut self) -> io::Result<()> {
+        if self.verify_bank_switch {
+            verify_bank_switch(
+                self.board,
+                (self.bank & 0xFF) as u8,
+                &mut self.last_bank_switch_sample,
+                &mut self.bank_switch_faults,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn maybe_retry_whole_bank(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let max_retries = match self.retry_whole_bank {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let base = self.cur_addr();
+        let mut previous = read_bank_window(self.board, base, MBC6_BANK_SIZE as u16)?;
+
+        for attempt in 0..max_retries {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            settle(self.board, base, self.settle_reads)?;
+
+            let current = read_bank_window(self.board, base, MBC6_BANK_SIZE as u16)?;
+
+            if current == previous {
+                return Ok(Some(current));
+            }
+
+            let bank = (self.bank & 0xFF) as u8;
+
+            eprintln!(
+                "warning: bank {} was unstable across whole-bank re-reads (attempt {}/{}); \
+                 re-selecting and reading the whole bank again",
+                self.bank, attempt + 1, max_retries
+            );
+
+            if !self.unstable_banks.contains(&bank) {
+                self.unstable_banks.push(bank);
+            }
+
+            previous = current;
+        }
+
+        eprintln!(
+            "warning: bank {} did not stabilize after {} whole-bank re-reads; using the last read",
+            self.bank, max_retries
+        );
+
+        Ok(Some(previous))
+    }
 }
 
-impl<'a> Read for Mbc3Reader<'a> {
+impl<'a> Read for Mbc6Reader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut n = 0;
 
+        if self.bank_select_strategy == BankSelectStrategy::PerChunk
+            && self.is_valid_addr(self.addr as i64)
+        {
+            self.select_rom_bank()
+                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+        }
+
         for data in buf.iter_mut() {
             if !self.is_valid_addr(self.addr as i64) {
                 break;
             }
 
-            if self.addr != 0 && self.addr % 0x4000 == 0 {
-                self.bank += 1;
+            if self.addr >= 0x4000 && (self.addr - 0x4000) % MBC6_BANK_SIZE == 0 {
+                self.bank = ((self.addr - 0x4000) / MBC6_BANK_SIZE) as u16 + 2;
 
                 self.select_rom_bank()
                     .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+
+                settle(self.board, self.cur_addr(), self.settle_reads)?;
+                self.maybe_verify_bank_switch()?;
+                self.bank_cache = self.maybe_retry_whole_bank()?;
             }
 
-            self.board.set_addr(self.cur_addr());
-            *data = self
-                .board
-                .read_byte()
-                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            *data = match &self.bank_cache {
+                Some(cache) => cache[(self.cur_addr() % MBC6_BANK_SIZE as u16) as usize],
+                None => {
+                    self.board.set_addr(self.cur_addr());
+                    read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?
+                }
+            };
 
             self.addr += 1;
             n += 1;
@@ -413,15 +3055,29 @@ impl<'a> Read for Mbc3Reader<'a> {
     }
 }
 
-pub struct Mbc5Reader<'a> {
+// 一部のブートレグ複数カートで使われるM161マッパー。0x0000-0x7FFFの
+// どこかへ1バイト書き込むとそのバンク番号がラッチされ、32KB全域
+// (0x0000-0x7FFF)が丸ごとそのバンクに差し替わる。このラッチはハード
+// ウェアリセット(RSTピン)でのみ解除される「ワンショット」レジスタで、
+// 2回目以降の書き込みは無視される -- 通常のMBCのように0x2000へ都度
+// 書き直すだけではバンクを進められず、切り替えるたびに`board.reset()`で
+// ラッチを解除してから選び直す必要がある。カートリッジタイプバイト
+// (0x0147)にM161専用のコードは割り当てられていない(大半のボードは
+// MBC1などの既知のコードを誤って名乗る)ため、自動検出はできず
+// `new_mbc_reader`の`m161_override`引数での明示指定でのみ選択される。
+const M161_BANK_SIZE: usize = 0x8000;
+
+pub struct M161Reader<'a> {
     board: &'a mut CubicStyleBoard,
 
     addr: u32,
     size: usize,
     bank: u16,
+    keep_going: bool,
+    faults: Vec<u32>,
 }
 
-impl<'a> MbcReader for Mbc5Reader<'a> {
+impl<'a> MbcReader for M161Reader<'a> {
     fn size(&self) -> usize {
         self.size
     }
@@ -429,37 +3085,50 @@ impl<'a> MbcReader for Mbc5Reader<'a> {
     fn status(&self) -> String {
         format!("BANK#{} {:#04X}", self.bank, self.cur_addr())
     }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
+
+    fn faults(&self) -> &[u32] {
+        &self.faults
+    }
 }
 
-impl<'a> Mbc5Reader<'a> {
-    pub fn new(board: &'a mut CubicStyleBoard, header: RomHeader) -> Self {
-        Self {
+impl<'a> M161Reader<'a> {
+    pub fn new(board: &'a mut CubicStyleBoard, header: RomHeader, keep_going: bool) -> Result<Self> {
+        let mut reader = Self {
             board,
             addr: 0,
             size: header.rom_size,
             bank: 0,
-        }
+            keep_going,
+            faults: Vec::new(),
+        };
+
+        reader.select_rom_bank()?;
+
+        Ok(reader)
     }
 
     fn cur_addr(&self) -> u16 {
-        (if self.addr >= 0x4000 {
-            self.addr % 0x4000 + 0x4000
-        } else {
-            self.addr
-        }) as u16
+        (self.addr % M161_BANK_SIZE as u32) as u16
     }
 
     fn select_rom_bank(&mut self) -> Result<()> {
-        let bank_low = (self.bank & 0xFF) as u8;
-        let bank_high = ((self.bank >> 8) & 0b00000001) as u8;
+        self.board.set_addr(0x0000);
+        self.board.write_byte(self.bank as u8)?;
 
-        self.board.set_addr(0x2000);
-        self.board.write_byte(bank_low)?;
+        Ok(())
+    }
 
-        self.board.set_addr(0x3000);
-        self.board.write_byte(bank_high)?;
+    // ラッチはワンショットのため、次のバンクへ進むにはリセットで
+    // 解除してから選び直す必要がある。
+    fn relatch_next_bank(&mut self) -> Result<()> {
+        self.bank += 1;
 
-        Ok(())
+        self.board.reset()?;
+        self.select_rom_bank()
     }
 
     fn is_valid_addr(&self, addr: i64) -> bool {
@@ -467,7 +3136,7 @@ impl<'a> Mbc5Reader<'a> {
     }
 }
 
-impl<'a> Read for Mbc5Reader<'a> {
+impl<'a> Read for M161Reader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut n = 0;
 
@@ -476,18 +3145,13 @@ impl<'a> Read for Mbc5Reader<'a> {
                 break;
             }
 
-            if self.addr != 0 && self.addr % 0x4000 == 0 {
-                self.bank += 1;
-
-                self.select_rom_bank()
+            if self.addr != 0 && self.addr as usize % M161_BANK_SIZE == 0 {
+                self.relatch_next_bank()
                     .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
             }
 
             self.board.set_addr(self.cur_addr());
-            *data = self
-                .board
-                .read_byte()
-                .map_err(|e| io::Error::new(ErrorKind::BrokenPipe, e))?;
+            *data = read_byte_or_fill(self.board, self.addr, self.keep_going, &mut self.faults)?;
 
             self.addr += 1;
             n += 1;
@@ -512,6 +3176,10 @@ impl<'a> MbcReader for ReplReader<'a> {
     fn status(&self) -> String {
         format!("MANUAL {:#04X}", self.addr)
     }
+
+    fn verify_logo(&mut self) -> Result<bool> {
+        read_logo_at_fixed_bank(self.board)
+    }
 }
 
 impl<'a> ReplReader<'a> {
@@ -0,0 +1,49 @@
+/// ユーザー向け文言の切り替え。デフォルトは英語で、`--lang ja`または
+/// `LANG`環境変数が`ja`始まりの場合は日本語になる。
+///
+/// 今のところ`read_rom_once`の主要な進捗メッセージのみがこのテーブルを
+/// 経由しており、それ以外の`println!`/`eprintln!`は従来通り直書きの
+/// 日本語/英語混在のまま。全箇所を一度に置き換えると差分が肥大化する
+/// ため、まずは最も頻繁に目にするROM読み込みの経路から始めている。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Ja,
+}
+
+impl Lang {
+    pub fn from_flag_or_env(flag: Option<&str>) -> Self {
+        let raw = flag
+            .map(|s| s.to_string())
+            .or_else(|| std::env::var("LANG").ok());
+
+        match raw {
+            Some(s) if s.to_lowercase().starts_with("ja") => Lang::Ja,
+            _ => Lang::En,
+        }
+    }
+}
+
+pub fn stage(lang: Lang, key: &str) -> &'static str {
+    match (lang, key) {
+        (Lang::Ja, "board_init") => "拡張ボードの初期化中...",
+        (Lang::En, "board_init") => "initializing the expansion board...",
+
+        (Lang::Ja, "header_parse") => "ROMヘッダの解析中...",
+        (Lang::En, "header_parse") => "parsing the ROM header...",
+
+        (Lang::Ja, "output_create") => "出力ファイルの作成中...",
+        (Lang::En, "output_create") => "creating the output file...",
+
+        (Lang::Ja, "reading_rom") => "ROM読み込み中...",
+        (Lang::En, "reading_rom") => "reading the ROM...",
+
+        (Lang::Ja, "finishing") => "仕上げ中...",
+        (Lang::En, "finishing") => "finishing up...",
+
+        (Lang::Ja, "done") => "完了！",
+        (Lang::En, "done") => "done!",
+
+        _ => "",
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `--report`が指定された時だけ、NDJSON(1行1JSONオブジェクト)で操作ログを
+/// 追記する。`manifest::DumpManifest`が「このダンプが何か」を記録するのに
+/// 対し、こちらは「この操作で何が起きたか」(各ステージのタイムスタンプ・
+/// ヘッダ情報・結果・エラー)を記録する運用監査向けのログで、両者は用途が
+/// 異なる。
+///
+/// 現時点で各ステージ単位の詳細なログを出しているのは`Read`のみ。他の
+/// サブコマンドは開始/結果の2行だけを記録する、より粗いカバレッジに
+/// とどまっている。
+pub struct ReportWriter {
+    file: Option<File>,
+}
+
+#[derive(Serialize)]
+struct ReportLine<'a> {
+    unix_time: u64,
+    event: &'a str,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    detail: Value,
+}
+
+impl ReportWriter {
+    pub fn new(path: Option<&str>) -> Result<Self> {
+        let file = match path {
+            Some(path) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .truncate(true)
+                    .write(true)
+                    .open(path)?,
+            ),
+            None => None,
+        };
+
+        Ok(Self { file })
+    }
+
+    pub fn log(&mut self, event: &str, detail: Value) {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => return,
+        };
+
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let line = ReportLine {
+            unix_time,
+            event,
+            detail,
+        };
+
+        if let Ok(json) = serde_json::to_string(&line) {
+            let _ = writeln!(file, "{}", json);
+        }
+    }
+}
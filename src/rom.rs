@@ -1,12 +1,15 @@
-use crate::mbc::RomHeaderReader;
+use crate::mbc::{RomHeaderReader, ROM_BANK_SIZE};
 use crate::utils::bytes_to_hex;
 use anyhow::{bail, Context, Result};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::io::{Read, Seek, SeekFrom};
+use std::str::FromStr;
 
-#[derive(FromPrimitive, Copy, Clone, Debug)]
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MbcType {
     RomOnly = 0x00,
     Mbc1 = 0x01,
@@ -29,6 +32,32 @@ pub enum MbcType {
     Mbc5Rumble = 0x1C,
     Mbc5RumbleRam = 0x1D,
     Mbc5RumbleRamBattery = 0x1E,
+
+    // 「がんばれゴエモン ネオモモタロウ電鉄でGO」(Net de Get系)のみで
+    // 使われた特殊なマッパー。通常のMBCと異なり、切り替え可能な2つの
+    // 8KB窓(0x4000-0x5FFF窓Aと0x6000-0x7FFF窓B)をそれぞれ独立した
+    // レジスタで選択する。フラッシュRAM(バックアップ用)も同様に窓A/B
+    // 独立のバンクレジスタを持つ(`mbc::Mbc6Reader`/`mbc::Mbc6RamReader`
+    // 参照)。書き込み(フラッシュの消去/プログラムコマンドシーケンス)は
+    // 未対応で今後の課題。
+    Mbc6 = 0x20,
+
+    // 傾きセンサー+振動+シリアルEEPROM(SRAMではない)。実在するのは
+    // Kirby's Tilt 'n' Tumble/Command Masterの2タイトルのみで、
+    // 0xA000-0xAFFF窓はSRAMではなく93LC56 EEPROMのビットバンギング
+    // 制御レジスタとして振る舞う(`mbc::EepromReader`参照)。
+    Mbc7SensorRumbleRamBattery = 0x22,
+
+    // GAME BOY CAMERA(MAC-GBD)。バンキングはMBC3相当だが、RAMバンク
+    // レジスタ(0x4000)のビット4を立てるとSRAMではなくカメラのCCD/画像
+    // レジスタ(0xA000-0xA0FF付近)がマップされる。バンク値をSRAM側の
+    // 0-15にしか書き込まない限り、このレジスタ領域とは干渉しない。
+    PocketCamera = 0xFC,
+
+    /// 上記のいずれの既知コードとも一致しないカートリッジタイプバイト。
+    /// ペイロード付きバリアントのため`num-derive`の`FromPrimitive`は
+    /// 使えず、代わりに`MbcType::from_header_byte`で手動マッピングする。
+    Unknown(u8),
 }
 
 impl Default for MbcType {
@@ -37,6 +66,143 @@ impl Default for MbcType {
     }
 }
 
+impl MbcType {
+    /// カートリッジタイプバイト(0x0147)を`MbcType`へ変換する。既知の
+    /// コードのいずれとも一致しなければ、そのバイト値を保持した
+    /// `MbcType::Unknown`を返す(こちらは決してエラーにしない -- 呼び出し
+    /// 元がヘッダ解析全体を打ち切るかどうかを判断する)。`Unknown`が
+    /// ペイロード付きバリアントのため、`num-derive`の`FromPrimitive`
+    /// (フィールド無しバリアントしか扱えない)は使わずここで手動判定する。
+    pub fn from_header_byte(b: u8) -> Self {
+        match b {
+            0x00 => MbcType::RomOnly,
+            0x01 => MbcType::Mbc1,
+            0x02 => MbcType::Mbc1Ram,
+            0x03 => MbcType::Mbc1RamBattery,
+            0x05 => MbcType::Mbc2,
+            0x06 => MbcType::Mbc2Battery,
+            0x08 => MbcType::RomRam,
+            0x09 => MbcType::RomRamBattery,
+            0x0b => MbcType::Mmm01,
+            0x0c => MbcType::Mmm01Ram,
+            0x0d => MbcType::Mmm01RamBattery,
+            0x10 => MbcType::Mbc3TimerRamBattery,
+            0x11 => MbcType::Mbc3,
+            0x12 => MbcType::Mbc3Ram,
+            0x13 => MbcType::Mbc3RamBattery,
+            0x19 => MbcType::Mbc5,
+            0x1A => MbcType::Mbc5Ram,
+            0x1B => MbcType::Mbc5RamBattery,
+            0x1C => MbcType::Mbc5Rumble,
+            0x1D => MbcType::Mbc5RumbleRam,
+            0x1E => MbcType::Mbc5RumbleRamBattery,
+            0x20 => MbcType::Mbc6,
+            0x22 => MbcType::Mbc7SensorRumbleRamBattery,
+            0xFC => MbcType::PocketCamera,
+            other => MbcType::Unknown(other),
+        }
+    }
+
+    /// このカートリッジタイプがバックアップ/内蔵RAMを持つと申告している
+    /// か。MBC2の内蔵512バイトRAMやGAME BOY CAMERAのSRAM、MBC7のシリアル
+    /// EEPROMのように、名前に"Ram"を含まない種別もRAMを持つ点に注意。
+    pub fn implies_ram(&self) -> bool {
+        matches!(
+            self,
+            MbcType::Mbc1Ram
+                | MbcType::Mbc1RamBattery
+                | MbcType::Mbc2
+                | MbcType::Mbc2Battery
+                | MbcType::RomRam
+                | MbcType::RomRamBattery
+                | MbcType::Mmm01Ram
+                | MbcType::Mmm01RamBattery
+                | MbcType::Mbc3TimerRamBattery
+                | MbcType::Mbc3Ram
+                | MbcType::Mbc3RamBattery
+                | MbcType::Mbc5Ram
+                | MbcType::Mbc5RamBattery
+                | MbcType::Mbc5RumbleRam
+                | MbcType::Mbc5RumbleRamBattery
+                | MbcType::Mbc6
+                | MbcType::Mbc7SensorRumbleRamBattery
+                | MbcType::PocketCamera
+        )
+    }
+}
+
+impl fmt::Display for MbcType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            MbcType::RomOnly => "ROM_ONLY",
+            MbcType::Mbc1 => "MBC1",
+            MbcType::Mbc1Ram => "MBC1+RAM",
+            MbcType::Mbc1RamBattery => "MBC1+RAM+BATTERY",
+            MbcType::Mbc2 => "MBC2",
+            MbcType::Mbc2Battery => "MBC2+BATTERY",
+            MbcType::RomRam => "ROM+RAM",
+            MbcType::RomRamBattery => "ROM+RAM+BATTERY",
+            MbcType::Mmm01 => "MMM01",
+            MbcType::Mmm01Ram => "MMM01+RAM",
+            MbcType::Mmm01RamBattery => "MMM01+RAM+BATTERY",
+            MbcType::Mbc3TimerRamBattery => "MBC3+TIMER+RAM+BATTERY",
+            MbcType::Mbc3 => "MBC3",
+            MbcType::Mbc3Ram => "MBC3+RAM",
+            MbcType::Mbc3RamBattery => "MBC3+RAM+BATTERY",
+            MbcType::Mbc5 => "MBC5",
+            MbcType::Mbc5Ram => "MBC5+RAM",
+            MbcType::Mbc5RamBattery => "MBC5+RAM+BATTERY",
+            MbcType::Mbc5Rumble => "MBC5+RUMBLE",
+            MbcType::Mbc5RumbleRam => "MBC5+RUMBLE+RAM",
+            MbcType::Mbc5RumbleRamBattery => "MBC5+RUMBLE+RAM+BATTERY",
+            MbcType::Mbc6 => "MBC6",
+            MbcType::Mbc7SensorRumbleRamBattery => "MBC7+SENSOR+RUMBLE+RAM+BATTERY",
+            MbcType::PocketCamera => "POCKET_CAMERA",
+            MbcType::Unknown(b) => return write!(f, "UNKNOWN(0x{:02X})", b),
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for MbcType {
+    type Err = anyhow::Error;
+
+    // "MBC3+RAM+BATTERY"のような正規名と、"mbc3-ram-battery"のような
+    // ケバブケースの両方を受け付ける。
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = s.to_uppercase().replace('-', "+");
+
+        Ok(match normalized.as_str() {
+            "ROM_ONLY" | "ROM" => MbcType::RomOnly,
+            "MBC1" => MbcType::Mbc1,
+            "MBC1+RAM" => MbcType::Mbc1Ram,
+            "MBC1+RAM+BATTERY" => MbcType::Mbc1RamBattery,
+            "MBC2" => MbcType::Mbc2,
+            "MBC2+BATTERY" => MbcType::Mbc2Battery,
+            "ROM+RAM" => MbcType::RomRam,
+            "ROM+RAM+BATTERY" => MbcType::RomRamBattery,
+            "MMM01" => MbcType::Mmm01,
+            "MMM01+RAM" => MbcType::Mmm01Ram,
+            "MMM01+RAM+BATTERY" => MbcType::Mmm01RamBattery,
+            "MBC3+TIMER+RAM+BATTERY" => MbcType::Mbc3TimerRamBattery,
+            "MBC3" => MbcType::Mbc3,
+            "MBC3+RAM" => MbcType::Mbc3Ram,
+            "MBC3+RAM+BATTERY" => MbcType::Mbc3RamBattery,
+            "MBC5" => MbcType::Mbc5,
+            "MBC5+RAM" => MbcType::Mbc5Ram,
+            "MBC5+RAM+BATTERY" => MbcType::Mbc5RamBattery,
+            "MBC5+RUMBLE" => MbcType::Mbc5Rumble,
+            "MBC5+RUMBLE+RAM" => MbcType::Mbc5RumbleRam,
+            "MBC5+RUMBLE+RAM+BATTERY" => MbcType::Mbc5RumbleRamBattery,
+            "MBC6" => MbcType::Mbc6,
+            "MBC7+SENSOR+RUMBLE+RAM+BATTERY" | "MBC7" => MbcType::Mbc7SensorRumbleRamBattery,
+            "POCKET_CAMERA" | "POCKET+CAMERA" => MbcType::PocketCamera,
+            other => bail!("unknown mbc type: {}", other),
+        })
+    }
+}
+
 #[derive(FromPrimitive, Copy, Clone, Debug)]
 pub enum DestinationCode {
     Japanese = 0x00,
@@ -50,11 +216,35 @@ impl Default for DestinationCode {
     }
 }
 
+// @see https://gbdev.io/pandocs/#the-cartridge-header (0104-0133, Nintendo Logo)
+pub const NINTENDO_LOGO: [u8; 0x0030] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+// SRAM/RTCレジスタウィンドウ(0xA000-0xBFFF)1バンク分のサイズ。
+pub const RAM_BANK_SIZE: usize = 0x2000;
+
+// MBC2内蔵バックアップRAMのサイズ(下位ニブルのみ有効な4bit RAM)。
+pub const MBC2_RAM_SIZE: usize = 0x0200;
+
+// GAME BOY CAMERAのSRAMサイズ。ヘッダのRAMサイズバイトは実際の搭載量
+// より小さく申告されていることが多いため、実測値を固定で使う。
+pub const POCKET_CAMERA_RAM_SIZE: usize = 128 * 1024;
+
+// MBC7カートリッジに載っている93LC56シリアルEEPROM(128 x 16bitワード)
+// のバイト数。ヘッダのRAMサイズバイトはこのEEPROMを申告していない
+// (MBC7カートリッジのRAMサイズは0x00になっている)ため、実測値を固定で
+// 使う。
+pub const MBC7_EEPROM_SIZE: usize = 256;
+
 #[derive(Copy, Clone)]
 pub struct RomHeader {
     pub entry_point: [u8; 4],
     pub logo: [u8; 0x0030],
     pub title: [u8; 0x0010],
+    pub cgb_flag: u8,
     pub new_licensee_code: [u8; 2],
     pub sgb_flag: bool,
     pub mbc_type: MbcType,
@@ -73,6 +263,7 @@ impl Default for RomHeader {
             entry_point: Default::default(),
             logo: [0; 0x0030],
             title: Default::default(),
+            cgb_flag: Default::default(),
             new_licensee_code: Default::default(),
             sgb_flag: Default::default(),
             mbc_type: Default::default(),
@@ -93,6 +284,7 @@ impl fmt::Debug for RomHeader {
             .field("entry_point", &bytes_to_hex(&self.entry_point[..]))
             .field("logo", &bytes_to_hex(&self.logo[..]))
             .field("title", &bytes_to_hex(&self.title[..]))
+            .field("cgb_flag", &format!("{:#04X}", self.cgb_flag))
             .field("new_licensee_code", &bytes_to_hex(&self.new_licensee_code))
             .field("sgb_flag", &self.sgb_flag)
             .field("mbc_type", &self.mbc_type)
@@ -107,7 +299,221 @@ impl fmt::Debug for RomHeader {
     }
 }
 
+/// 0x0134-0x014Cの25バイトからヘッダチェックサムを計算する。
+/// @see https://gbdev.io/pandocs/#014d-header-checksum
+pub fn compute_header_checksum(title_through_version: &[u8]) -> u8 {
+    title_through_version
+        .iter()
+        .fold(0u8, |acc, &b| acc.wrapping_sub(b).wrapping_sub(1))
+}
+
+/// 0x0134-0x014Cのうち1バイトだけ書き換えるとヘッダチェックサムが
+/// 一致するようになる修正候補。物理カートリッジの修理で、破損している
+/// 可能性が高いバイトの当たりを付ける診断用で、実際の書き換えは行わない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumRepairCandidate {
+    /// 実アドレス(0x0134-0x014C)。
+    pub address: u16,
+    pub original: u8,
+    pub replacement: u8,
+}
+
+/// `title_through_version`(0x0134-0x014Cの25バイト)のうち1バイトだけを
+/// 総当たりで書き換え、`compute_header_checksum`が`expected_checksum`と
+/// 一致するようになる組み合わせをすべて列挙する。1バイトごとにチェック
+/// サムを一致させる値はちょうど1つに決まるため、チェックサムが一致して
+/// いなければ25バイトそれぞれに1件ずつ、計25件の候補が返る(一致して
+/// いれば空)。
+pub fn find_checksum_repair_candidates(
+    title_through_version: &[u8; 0x0019],
+    expected_checksum: u8,
+) -> Vec<ChecksumRepairCandidate> {
+    let mut candidates = Vec::new();
+
+    for (offset, &original) in title_through_version.iter().enumerate() {
+        for replacement in 0u16..=0xFF {
+            let replacement = replacement as u8;
+
+            if replacement == original {
+                continue;
+            }
+
+            let mut modified = *title_through_version;
+            modified[offset] = replacement;
+
+            if compute_header_checksum(&modified) == expected_checksum {
+                candidates.push(ChecksumRepairCandidate {
+                    address: 0x0134 + offset as u16,
+                    original,
+                    replacement,
+                });
+            }
+        }
+    }
+
+    candidates
+}
+
+/// ROMサイズバイト(0x0148)の全既知コードから、バンク数(1バンク=
+/// `ROM_BANK_SIZE`=16KB)への対応表。0x00-0x08は正式仕様(32KB-8MB、
+/// 2-512バンク)、0x52-0x54は仕様外だが一部のカートリッジで実際に使われる
+/// 非2のべき乗のバンク数(72/80/96)。ここを唯一の対応表とすることで、
+/// ヘッダ解析時のバイト数算出と`rom_bank_count()`が食い違う余地をなくす。
+fn rom_size_code_bank_count(code: u8) -> Option<usize> {
+    match code {
+        0x00..=0x08 => Some(2usize << code),
+        0x52 => Some(72),
+        0x53 => Some(80),
+        0x54 => Some(96),
+        _ => None,
+    }
+}
+
 impl RomHeader {
+    pub fn is_valid_logo(&self) -> bool {
+        self.logo == NINTENDO_LOGO
+    }
+
+    /// バンク数は`rom_size`を単純に整数除算するのではなく、切り上げ除算で求める。
+    /// `--probe-rom-size`や壊れたヘッダなど、`rom_size`がバンク境界
+    /// ちょうどでない値に化けているケースでも、実際に読むべきバンク数を
+    /// 過小に切り捨てない(単純な整数除算では末尾の半端なバンクが
+    /// 消えてしまう)。
+    pub fn rom_bank_count(&self) -> usize {
+        (self.rom_size + ROM_BANK_SIZE - 1) / ROM_BANK_SIZE
+    }
+
+    /// 実際のSRAMバイト数。MBC2はヘッダのRAMサイズバイトが常に0x00
+    /// (RAM無し扱い)でも、内蔵の512バイト(下位ニブルのみ有効)の
+    /// バックアップRAMを持つため、ここで特別扱いする。GAME BOY CAMERAも
+    /// 同様にヘッダの申告値が実搭載量(128KB)と食い違うため固定値を返す。
+    pub fn ram_size_bytes(&self) -> usize {
+        match self.mbc_type {
+            MbcType::Mbc2 | MbcType::Mbc2Battery => MBC2_RAM_SIZE,
+            MbcType::PocketCamera => POCKET_CAMERA_RAM_SIZE,
+            MbcType::Mbc7SensorRumbleRamBattery => MBC7_EEPROM_SIZE,
+            _ => self.ram_size,
+        }
+    }
+
+    /// カートリッジタイプバイトとRAMサイズバイトの整合性を確認する。
+    /// 不整合を検出した場合、`trust_header_sizes`が偽(既定)なら
+    /// カートリッジタイプ側を信用し、「RAM無し」を示すタイプなのに
+    /// RAMサイズバイトが非0であれば`ram_size`を0へ補正する(ブート
+    /// レグ等でRAMサイズバイトだけが化けているケースへの対処)。真の
+    /// 場合はヘッダの申告値をそのまま残し、警告のみ行う。返り値は
+    /// 検出した不整合の説明(複数ある場合はその数だけ)。
+    pub fn reconcile(&mut self, trust_header_sizes: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let type_implies_ram = self.mbc_type.implies_ram();
+        let declared_ram_nonzero = self.ram_size != 0;
+
+        if type_implies_ram && !declared_ram_nonzero {
+            warnings.push(format!(
+                "cartridge type {} implies RAM, but the RAM size byte declares 0 bytes",
+                self.mbc_type
+            ));
+        } else if !type_implies_ram && declared_ram_nonzero {
+            warnings.push(format!(
+                "cartridge type {} implies no RAM, but the RAM size byte declares {} bytes -- {}",
+                self.mbc_type,
+                self.ram_size,
+                if trust_header_sizes {
+                    "keeping the declared size (--trust-header-sizes)"
+                } else {
+                    "trusting the cartridge type and treating this cartridge as having no RAM"
+                }
+            ));
+
+            if !trust_header_sizes {
+                self.ram_size = 0;
+            }
+        }
+
+        warnings
+    }
+
+    /// RAMバンク数。8KB未満のRAM(MBC2の512バイトや2KBカート)は
+    /// 1バンクとして数える。
+    pub fn ram_bank_count(&self) -> usize {
+        let size = self.ram_size_bytes();
+
+        if size == 0 {
+            0
+        } else {
+            (size + RAM_BANK_SIZE - 1) / RAM_BANK_SIZE
+        }
+    }
+
+    /// CGB Flagが0xC0(CGB専用)かどうか。0x80(CGB強化/DMG互換)は
+    /// 対象外で、`.gb`のままでよい。
+    pub fn is_cgb_only(&self) -> bool {
+        self.cgb_flag == 0xC0
+    }
+
+    /// タイトル領域(0x0134-0x0143)は、後年のカートリッジではその末尾が
+    /// マニュファクチャコード(0x013F-0x0142)とCGBフラグ(0x0143)に
+    /// 転用されており、実際のタイトル文字数は16/15/11文字のいずれかに
+    /// なる。CGBフラグの上位ビットが立っており、かつマニュファクチャ
+    /// コード領域がASCII英大文字/数字/ヌルのみで構成されていれば11文字、
+    /// CGBフラグのみ立っていれば15文字、どちらでもなければ旧来通り
+    /// 16文字として扱う。末尾のヌル/空白パディングは取り除く。
+    pub fn title_str(&self) -> String {
+        let manufacturer_code = &self.title[0x0B..0x0F];
+        let looks_like_manufacturer_code = manufacturer_code
+            .iter()
+            .all(|&b| b == 0 || b.is_ascii_uppercase() || b.is_ascii_digit());
+
+        let cgb_flagged = self.cgb_flag & 0x80 != 0;
+
+        let len = if cgb_flagged && looks_like_manufacturer_code {
+            0x0B
+        } else if cgb_flagged {
+            0x0F
+        } else {
+            0x10
+        };
+
+        let raw = &self.title[..len];
+        let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+
+        String::from_utf8_lossy(&raw[..end]).trim_end().to_string()
+    }
+
+    /// 旧ライセンシーコードの生値(16進2桁)。マッピング後の`publisher_str()`
+    /// とは別に、ヘッダの検証(自作/改造ROMの識別など)で生の値そのものが
+    /// 必要な場合に使う。
+    pub fn old_licensee_str(&self) -> String {
+        format!("0x{:02X}", self.old_licensee_code)
+    }
+
+    /// 新ライセンシーコードの生値(2文字のASCII数字)。`old_licensee_code`が
+    /// 0x33でない大半のカートリッジではこの値は未使用(0x00 0x00等)だが、
+    /// `publisher_str()`が内部で何を見て判定したかを利用者が確認できるよう
+    /// 生値のまま返す。
+    pub fn new_licensee_str(&self) -> String {
+        String::from_utf8_lossy(&self.new_licensee_code).to_string()
+    }
+
+    /// パブリッシャ名。`old_licensee_code`が0x33の場合は新ライセンシー
+    /// コード(`new_licensee_code`、2桁のASCII数字)を、それ以外は旧
+    /// ライセンシーコードを見る。表は主要なパブリッシャのみをカバーする
+    /// 非網羅的なものなので、未知のコードは値そのものを返す。
+    pub fn publisher_str(&self) -> String {
+        if self.old_licensee_code == 0x33 {
+            let code = String::from_utf8_lossy(&self.new_licensee_code).to_string();
+
+            new_licensee_name(&code)
+                .map(|s| s.to_string())
+                .unwrap_or(code)
+        } else {
+            old_licensee_name(self.old_licensee_code)
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("0x{:02X}", self.old_licensee_code))
+        }
+    }
+
     pub fn from_reader(reader: &mut RomHeaderReader) -> Result<Self> {
         let mut rom = Self::default();
 
@@ -124,6 +530,9 @@ impl RomHeader {
         // 0134-0143 - Title
         reader.read_exact(&mut rom.title[..])?;
 
+        // 0143 - CGB Flag (Titleの最終バイトと重複する領域)
+        rom.cgb_flag = rom.title[0x0F];
+
         // 0144-0145 - New Licensee Code
         reader.read_exact(&mut rom.new_licensee_code[..])?;
 
@@ -142,22 +551,29 @@ impl RomHeader {
 
         // 0147 - Cartridge Type
         if let Some(Ok(typ)) = reader.take(1).bytes().next() {
-            rom.mbc_type = FromPrimitive::from_u8(typ).context("unknown mbc type")?;
+            rom.mbc_type = MbcType::from_header_byte(typ);
+
+            if let MbcType::Unknown(b) = rom.mbc_type {
+                eprintln!(
+                    "unknown Cartridge Type {:#04X}; will attempt a conservative ROM-only dump -- \
+                     if you know the real mapper, force it with --mbc",
+                    b
+                );
+            }
         } else {
             bail!("failed to parse the Cardridge Type");
         }
 
         // 0148 - ROM Size
         rom.rom_size = match reader.take(1).bytes().next() {
-            Some(Ok(n @ 0x00..=0x08)) => ((32 * 1024) << n) as usize,
-            Some(Ok(0x52)) => (1.1 * 1024.0 * 1024.0) as usize,
-            Some(Ok(0x53)) => (1.2 * 1024.0 * 1024.0) as usize,
-            Some(Ok(0x54)) => (1.5 * 1024.0 * 1024.0) as usize,
-            Some(Ok(unknown)) => {
-                eprintln!("unknown ROM Size {:#X}", unknown);
+            Some(Ok(code)) => match rom_size_code_bank_count(code) {
+                Some(banks) => banks * ROM_BANK_SIZE,
+                None => {
+                    eprintln!("unknown ROM Size {:#X}", code);
 
-                0
-            }
+                    0
+                }
+            },
             Some(Err(e)) => bail!("error occured while reading the ROM Size {}", e),
             None => bail!("unexpected EOF while reading the ROM Size"),
         };
@@ -165,11 +581,11 @@ impl RomHeader {
         // 0149 - RAM Size
         rom.ram_size = match reader.take(1).bytes().next() {
             Some(Ok(0x00)) => 0_usize,
-            Some(Ok(0x01)) => 2 * 1024 * 1024_usize,
-            Some(Ok(0x02)) => 8 * 1024 * 1024_usize,
-            Some(Ok(0x03)) => 32 * 1024 * 1024_usize,
-            Some(Ok(0x04)) => 128 * 1024 * 1024_usize,
-            Some(Ok(0x05)) => 64 * 1024 * 1024_usize,
+            Some(Ok(0x01)) => 2 * 1024_usize,
+            Some(Ok(0x02)) => 8 * 1024_usize,
+            Some(Ok(0x03)) => 32 * 1024_usize,
+            Some(Ok(0x04)) => 128 * 1024_usize,
+            Some(Ok(0x05)) => 64 * 1024_usize,
             Some(Ok(unknown)) => {
                 eprintln!("unknown RAM Size {:#X}", unknown);
 
@@ -216,15 +632,12 @@ impl RomHeader {
 
         reader.seek(SeekFrom::Start(0x0134))?;
 
-        let mut chksum: u8 = 0;
+        let mut title_through_version = [0u8; 0x0019];
+        reader
+            .read_exact(&mut title_through_version)
+            .context("error occured while checking header chksum")?;
 
-        for _ in 0x0134..=0x014C {
-            if let Some(Ok(b)) = reader.take(1).bytes().next() {
-                chksum = chksum.wrapping_sub(b).wrapping_sub(1);
-            } else {
-                bail!("error occured while checking header chksum");
-            }
-        }
+        let chksum = compute_header_checksum(&title_through_version);
 
         if rom.header_checksum != chksum {
             bail!(
@@ -237,3 +650,296 @@ impl RomHeader {
         Ok(rom)
     }
 }
+
+/// 旧ライセンシーコード(0x014B)から主要なパブリッシャ名を引く。
+/// @see https://gbdev.io/pandocs/#014b-old-licensee-code
+/// この表は0x33(新ライセンシーコードを使う)を除き、よく見る値のみを
+/// 収録した非網羅的なものである。
+fn old_licensee_name(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x00 => "None",
+        0x01 => "Nintendo",
+        0x08 => "Capcom",
+        0x09 => "Hot-B",
+        0x0A => "Jaleco",
+        0x0B => "Coconuts Japan",
+        0x0C => "Elite Systems",
+        0x13 => "Electronic Arts",
+        0x18 => "Hudson Soft",
+        0x19 => "ITC Entertainment",
+        0x1A => "Yanoman",
+        0x1D => "Clary",
+        0x1F => "Virgin",
+        0x24 => "PCM Complete",
+        0x25 => "San-X",
+        0x28 => "Kotobuki Systems",
+        0x29 => "Seta",
+        0x30 => "Infogrames",
+        0x31 => "Nintendo",
+        0x32 => "Bandai",
+        0x34 => "Konami",
+        0x35 => "Hector",
+        0x38 => "Capcom",
+        0x39 => "Banpresto",
+        0x3C => "Entertainment i",
+        0x3E => "Gremlin",
+        0x41 => "Ubi Soft",
+        0x42 => "Atlus",
+        0x44 => "Malibu",
+        0x46 => "Angel",
+        0x47 => "Spectrum Holobyte",
+        0x49 => "Irem",
+        0x4A => "Virgin",
+        0x4D => "Malibu",
+        0x4F => "U.S. Gold",
+        0x50 => "Absolute",
+        0x51 => "Acclaim",
+        0x52 => "Activision",
+        0x53 => "American Sammy",
+        0x54 => "Gametek",
+        0x55 => "Park Place",
+        0x56 => "LJN",
+        0x57 => "Matchbox",
+        0x59 => "Milton Bradley",
+        0x5A => "Mindscape",
+        0x5B => "Romstar",
+        0x5C => "Naxat Soft",
+        0x5D => "Tradewest",
+        0x60 => "Titus",
+        0x61 => "Virgin",
+        0x67 => "Ocean",
+        0x69 => "Electronic Arts",
+        0x6E => "Elite Systems",
+        0x6F => "Electro Brain",
+        0x70 => "Infogrames",
+        0x71 => "Interplay",
+        0x72 => "Broderbund",
+        0x73 => "Sculptured Soft",
+        0x75 => "The Sales Curve",
+        0x78 => "t.hq",
+        0x79 => "Accolade",
+        0x7A => "Triffix Entertainment",
+        0x7C => "Microprose",
+        0x7F => "Kemco",
+        0x80 => "Misawa Entertainment",
+        0x83 => "Lozc",
+        0x86 => "Tokuma Shoten Intermedia",
+        0x8B => "Bullet-Proof Software",
+        0x8C => "Vic Tokai",
+        0x8E => "Ape",
+        0x8F => "I'Max",
+        0x91 => "Chunsoft",
+        0x92 => "Video System",
+        0x93 => "Tsubaraya Productions",
+        0x95 => "Varie",
+        0x96 => "Yonezawa/S'pal",
+        0x97 => "Kaneko",
+        0x99 => "Arc",
+        0x9A => "Nihon Bussan",
+        0x9B => "Tecmo",
+        0x9C => "Imagineer",
+        0x9D => "Banpresto",
+        0x9F => "Nova",
+        0xA1 => "Hori Electric",
+        0xA2 => "Bandai",
+        0xA4 => "Konami",
+        0xA6 => "Kawada",
+        0xA7 => "Takara",
+        0xA9 => "Technos Japan",
+        0xAA => "Broderbund",
+        0xAC => "Toei Animation",
+        0xAD => "Toho",
+        0xAF => "Namco",
+        0xB0 => "Acclaim",
+        0xB1 => "ASCII or Nexsoft",
+        0xB2 => "Bandai",
+        0xB4 => "Square Enix",
+        0xB6 => "HAL Laboratory",
+        0xB7 => "SNK",
+        0xB9 => "Pony Canyon",
+        0xBA => "Culture Brain",
+        0xBB => "Sunsoft",
+        0xBD => "Sony Imagesoft",
+        0xBF => "Sammy",
+        0xC0 => "Taito",
+        0xC2 => "Kemco",
+        0xC3 => "Squaresoft",
+        0xC4 => "Tokuma Shoten Intermedia",
+        0xC5 => "Data East",
+        0xC6 => "Tonkin House",
+        0xC8 => "Koei",
+        0xC9 => "UFL",
+        0xCA => "Ultra",
+        0xCB => "Vap",
+        0xCC => "Use",
+        0xCD => "Meldac",
+        0xCE => "Pony Canyon",
+        0xCF => "Angel",
+        0xD0 => "Taito",
+        0xD1 => "Sofel",
+        0xD2 => "Quest",
+        0xD3 => "Sigma Enterprises",
+        0xD4 => "Ask Kodansha",
+        0xD6 => "Naxat Soft",
+        0xD7 => "Copya System",
+        0xD9 => "Banpresto",
+        0xDA => "Tomy",
+        0xDB => "LJN",
+        0xDD => "NCS",
+        0xDE => "Human",
+        0xDF => "Altron",
+        0xE0 => "Jaleco",
+        0xE1 => "Towa Chiki",
+        0xE2 => "Yutaka",
+        0xE3 => "Varie",
+        0xE5 => "Epcoh",
+        0xE7 => "Athena",
+        0xE8 => "Asmik ACE Entertainment",
+        0xE9 => "Natsume",
+        0xEA => "King Records",
+        0xEB => "Atlus",
+        0xEC => "Epic/Sony Records",
+        0xEE => "IGS",
+        0xF0 => "A Wave",
+        0xF3 => "Extreme Entertainment",
+        0xFF => "LJN",
+        _ => return None,
+    })
+}
+
+/// 新ライセンシーコード(0x0144-0x0145、ASCII2桁)から主要なパブリッシャ
+/// 名を引く。@see https://gbdev.io/pandocs/#0144-0145-new-licensee-code
+/// この表もよく見る値のみを収録した非網羅的なものである。
+fn new_licensee_name(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "00" => "None",
+        "01" => "Nintendo",
+        "08" => "Capcom",
+        "13" => "Electronic Arts",
+        "18" => "Hudson Soft",
+        "19" => "b-ai",
+        "20" => "KSS",
+        "22" => "POW",
+        "24" => "PCM Complete",
+        "25" => "San-X",
+        "28" => "Kemco Japan",
+        "29" => "Seta",
+        "30" => "Viacom",
+        "31" => "Nintendo",
+        "32" => "Bandai",
+        "33" => "Ocean/Acclaim",
+        "34" => "Konami",
+        "35" => "Hector",
+        "37" => "Taito",
+        "38" => "Hudson",
+        "39" => "Banpresto",
+        "41" => "Ubi Soft",
+        "42" => "Atlus",
+        "44" => "Malibu",
+        "46" => "Angel",
+        "47" => "Bullet-Proof",
+        "49" => "Irem",
+        "50" => "Absolute",
+        "51" => "Acclaim",
+        "52" => "Activision",
+        "53" => "American Sammy",
+        "54" => "Konami",
+        "55" => "Hi Tech Entertainment",
+        "56" => "LJN",
+        "57" => "Matchbox",
+        "58" => "Mattel",
+        "59" => "Milton Bradley",
+        "60" => "Titus",
+        "61" => "Virgin",
+        "64" => "LucasArts",
+        "67" => "Ocean",
+        "69" => "Electronic Arts",
+        "70" => "Infogrames",
+        "71" => "Interplay",
+        "72" => "Broderbund",
+        "73" => "Sculptured Soft",
+        "75" => "The Sales Curve",
+        "78" => "t.hq",
+        "79" => "Accolade",
+        "80" => "Misawa Entertainment",
+        "83" => "Lozc",
+        "86" => "Tokuma Shoten Intermedia",
+        "87" => "Tsukuda Original",
+        "91" => "Chunsoft",
+        "92" => "Video System",
+        "93" => "Ocean/Acclaim",
+        "95" => "Varie",
+        "96" => "Yonezawa/S'pal",
+        "97" => "Kaneko",
+        "99" => "Pack-in-Video",
+        "9H" => "Bottom Up",
+        "A4" => "Konami (Yu-Gi-Oh!)",
+        "B1" => "Nexsoft",
+        "C8" => "Koei",
+        "CE" => "Pony Canyon",
+        "D9" => "Banpresto",
+        "DK" => "Kodansha",
+        "EL" => "Spike",
+        "FR" => "Digital Tainment Pool",
+        "GD" => "Square Enix",
+        "PB" => "Playbox",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rom_size_code_bank_count_covers_standard_and_oversize_codes() {
+        assert_eq!(rom_size_code_bank_count(0x00), Some(2));
+        assert_eq!(rom_size_code_bank_count(0x08), Some(512));
+        assert_eq!(rom_size_code_bank_count(0x52), Some(72));
+        assert_eq!(rom_size_code_bank_count(0x53), Some(80));
+        assert_eq!(rom_size_code_bank_count(0x54), Some(96));
+        assert_eq!(rom_size_code_bank_count(0x09), None);
+    }
+
+    #[test]
+    fn rom_bank_count_rounds_up_a_partial_bank() {
+        let mut header = RomHeader::default();
+        header.rom_size = ROM_BANK_SIZE + 1;
+
+        assert_eq!(header.rom_bank_count(), 2);
+    }
+
+    #[test]
+    fn ram_size_bytes_uses_fixed_size_for_pocket_camera() {
+        let mut header = RomHeader::default();
+        header.mbc_type = MbcType::PocketCamera;
+        header.ram_size = 0;
+
+        assert_eq!(header.ram_size_bytes(), POCKET_CAMERA_RAM_SIZE);
+    }
+
+    #[test]
+    fn mbc_type_display_and_from_str_round_trip() {
+        let types = [
+            MbcType::RomOnly,
+            MbcType::Mbc1RamBattery,
+            MbcType::Mbc3TimerRamBattery,
+            MbcType::Mbc5RumbleRamBattery,
+            MbcType::Mbc7SensorRumbleRamBattery,
+            MbcType::PocketCamera,
+        ];
+
+        for mbc_type in types {
+            let name = mbc_type.to_string();
+            assert_eq!(name.parse::<MbcType>().unwrap(), mbc_type);
+        }
+    }
+
+    #[test]
+    fn mbc_type_from_str_accepts_kebab_case() {
+        assert_eq!(
+            "mbc3-ram-battery".parse::<MbcType>().unwrap(),
+            MbcType::Mbc3RamBattery
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// RAMダンプ内の1セーブスロットの範囲(RAM先頭からのオフセットとバイト長)。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotRange {
+    pub name: String,
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// タイトルごとのセーブスロットレイアウト定義。正確なオフセットは
+/// タイトル・リビジョンごとに異なり、誤ったプロファイルはセーブデータを
+/// 誤って分割する恐れがあるため、このクレートは組み込みのプロファイルを
+/// 一切同梱しない。利用者が自分のタイトル用に検証済みのJSONファイルを
+/// `--slots`へ渡すこと。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveProfile {
+    pub title: String,
+    pub slots: Vec<SlotRange>,
+}
+
+pub fn load_profile(path: &str) -> Result<SaveProfile> {
+    let json = fs::read_to_string(path)?;
+
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// スロットの中身がすべて同じバイト値(0x00や0xFF)であれば未使用と判定する。
+/// あくまでヒューリスティックで、有効なチェックサムを持つ空スロットを
+/// 誤検出する可能性はゼロではない。
+pub fn slot_looks_empty(data: &[u8]) -> bool {
+    match data.first() {
+        Some(&first) => data.iter().all(|&b| b == first),
+        None => true,
+    }
+}
@@ -1,6 +1,668 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// `--no-progress`指定時、または標準エラーがTTYでない(リダイレクト/CI)
+/// 場合に`false`を返す。`\r`で上書きするアニメーション付きバーは
+/// ログファイルやCIの出力に大量のノイズを残すため、そうした環境では
+/// 自動的に無効化し、`ProgressReporter`側で一定間隔のパーセンテージ
+/// 出力にフォールバックする。
+pub fn progress_bar_enabled(no_progress: bool) -> bool {
+    !no_progress && io::stderr().is_terminal()
+}
+
+/// `indicatif`の進捗バーのラッパー。`show_bar`が偽の場合はバーを描画せず、
+/// 代わりに10%刻みで`println!`による進捗行を出す。呼び出し側は
+/// `ProgressBar`とほぼ同じ見た目のAPI(`inc`/`set_message`/
+/// `finish_and_clear`)をそのまま使える。
+pub struct ProgressReporter {
+    bar: Option<ProgressBar>,
+    total: u64,
+    current: u64,
+    last_reported_percent: u64,
+}
+
+impl ProgressReporter {
+    pub fn new(total: u64, template: &str, show_bar: bool) -> Self {
+        let bar = if show_bar {
+            let bar = ProgressBar::new(total);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template(template)
+                    .progress_chars("#>-"),
+            );
+            Some(bar)
+        } else {
+            None
+        };
+
+        Self {
+            bar,
+            total,
+            current: 0,
+            last_reported_percent: 0,
+        }
+    }
+
+    pub fn inc(&mut self, delta: u64) {
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+            return;
+        }
+
+        self.current += delta;
+
+        let percent = if self.total == 0 {
+            100
+        } else {
+            (self.current * 100 / self.total).min(100)
+        };
+
+        if percent >= self.last_reported_percent + 10
+            || (percent == 100 && self.last_reported_percent < 100)
+        {
+            println!("progress: {}% ({}/{})", percent, self.current, self.total);
+            self.last_reported_percent = percent;
+        }
+    }
+
+    pub fn set_message(&self, msg: &str) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(msg);
+        }
+    }
+
+    pub fn finish_and_clear(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// `--json-progress`未指定時のスロットル間隔未満での連投を防ぐための下限。
+/// GUIラッパー側の再描画コストを考えると、これより細かい粒度は不要。
+const JSON_PROGRESS_MIN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// `--json-progress`指定時に、進捗を標準エラーへNDJSON(1行1件のJSON)で
+/// 書き出す。人間向けの`ProgressReporter`(進捗バー/10%刻みのテキスト)とは
+/// 独立に併用でき、Electron/Tauri等でCLIをラップするフロントエンドが端末の
+/// 描画をスクレイピングせず構造化された進捗を購読できるようにするための
+/// もの。`--report`用の`ReportWriter`と違いファイルではなく標準エラーへ
+/// 常時流すため、間引かないとバイト単位で大量にフラッディングしてしまう。
+pub struct JsonProgressEmitter {
+    enabled: bool,
+    last_emitted: Option<Instant>,
+}
+
+impl JsonProgressEmitter {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last_emitted: None,
+        }
+    }
+
+    /// `bytes == total`(完了)の場合はスロットルに関わらず必ず出力する。
+    pub fn emit(&mut self, stage: &str, bytes: u64, total: u64, status: &str, bytes_per_sec: f64) {
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        let due = match self.last_emitted {
+            Some(last) => now.duration_since(last) >= JSON_PROGRESS_MIN_INTERVAL,
+            None => true,
+        };
+
+        if !due && bytes < total {
+            return;
+        }
+
+        self.last_emitted = Some(now);
+
+        let percent = if total == 0 { 100 } else { (bytes * 100 / total).min(100) };
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        eprintln!(
+            "{{\"unix_time\":{},\"stage\":\"{}\",\"bytes\":{},\"total\":{},\"percent\":{},\"bytes_per_sec\":{},\"status\":\"{}\"}}",
+            unix_time,
+            stage,
+            bytes,
+            total,
+            percent,
+            bytes_per_sec as u64,
+            status.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+    }
+}
+
+/// UNIXエポック秒を`YYYYMMDDTHHMMSSZ`(コロンやスラッシュを含まない、
+/// ファイル名に安全なISO-8601風のUTC表記)に変換する。日付処理クレートを
+/// 追加しないため、うるう年を考慮したカレンダー計算を自前で行っている。
+pub fn format_timestamp_for_filename(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
+
+// Howard HinnantのAlgorithm `civil_from_days`。エポック(1970-01-01)からの
+// 経過日数をグレゴリオ暦のUTC年月日へ変換する。
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = if m <= 2 { y + 1 } else { y };
+
+    (y, m, d)
+}
+
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
     bytes
         .iter()
         .map(|&b| format!("{:02X}", b))
         .collect::<String>()
 }
+
+/// `--ascii-dump`向けの、1行16バイトのオフセット・16進数・ASCII列を
+/// 並べた古典的なhexdump形式。手作業でヘッダ/セーブ領域を目視確認する時に
+/// 生バイナリや裸の16進数の羅列より読みやすくするためのもの。印字不可能な
+/// バイト(0x20未満・0x7E超)は`.`で代用する。
+pub fn format_hex_ascii_dump(base_addr: usize, data: &[u8]) -> String {
+    let mut output = String::new();
+
+    for (i, chunk) in data.chunks(16).enumerate() {
+        let offset = base_addr + i * 16;
+
+        let hex = chunk
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if (0x20..=0x7E).contains(&b) { b as char } else { '.' })
+            .collect();
+
+        output.push_str(&format!("{:06X}  {:<47}  {}\n", offset, hex, ascii));
+    }
+
+    output
+}
+
+/// 指数加重移動平均。`indicatif`のデフォルトETAはスループットの瞬間値に
+/// 引きずられて大きく振れるため、進捗バーのメッセージに載せるETAは
+/// これで平滑化したスループットから算出する。
+pub struct Ewma {
+    smoothing: f64,
+    value: Option<f64>,
+}
+
+impl Ewma {
+    pub fn new(smoothing: f64) -> Self {
+        Self {
+            smoothing,
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, sample: f64) -> f64 {
+        let value = match self.value {
+            Some(prev) => self.smoothing * sample + (1.0 - self.smoothing) * prev,
+            None => sample,
+        };
+
+        self.value = Some(value);
+
+        value
+    }
+}
+
+/// `bank_size`ごとに`data`を分割し、上位バンクが下位バンクの繰り返し
+/// (ミラー)になっている場合、実際に搭載されている物理サイズを推定する。
+/// ミラーが見つからなければ`data.len()`をそのまま返す。
+pub fn detect_mirrored_size(data: &[u8], bank_size: usize) -> usize {
+    if bank_size == 0 || data.len() <= bank_size {
+        return data.len();
+    }
+
+    let banks = data.len() / bank_size;
+
+    for candidate in 1..banks {
+        let mirrored = (0..banks).all(|bank| {
+            data[bank * bank_size..(bank + 1) * bank_size]
+                == data[(bank % candidate) * bank_size..(bank % candidate + 1) * bank_size]
+        });
+
+        if mirrored {
+            return candidate * bank_size;
+        }
+    }
+
+    data.len()
+}
+
+/// バンク内でアドレス線A13が結線されていないボートレグ基板は、8KBの
+/// SRAMウィンドウの前半4KB(0xA000-0xAFFF)と後半4KB(0xB000-0xBFFF)に
+/// 同じ内容が現れる。1バンク分の`bank`スライスを受け取り、そのような
+/// 半分ミラーが見つかった場合は前半のサイズ(`bank.len() / 2`)を返す。
+/// ミラーが見つからない、またはバンクが奇数長・空の場合は`None`。
+pub fn detect_half_bank_mirror(bank: &[u8]) -> Option<usize> {
+    if bank.is_empty() || bank.len() % 2 != 0 {
+        return None;
+    }
+
+    let half = bank.len() / 2;
+
+    if bank[..half] == bank[half..] {
+        Some(half)
+    } else {
+        None
+    }
+}
+
+/// `--timings`用に、ステージごとの経過時間を記録する。`mark`を呼ぶたびに
+/// 直前の`mark`(または`new`)からの経過時間をそのステージ名に対して
+/// 記録し、次のステージの計測へ進む。トランスポート(バンク読み出し)・
+/// ディスクI/O(ファイル書き込み)・ハッシュ計算のどれがボトルネックかを
+/// 切り分けるための診断用で、`--timings`未指定時は`mark`が何もしない
+/// ため計測コストはない。
+pub struct Timings {
+    enabled: bool,
+    started: Instant,
+    last: Instant,
+    stages: Vec<(String, Duration)>,
+}
+
+impl Timings {
+    pub fn new(enabled: bool) -> Self {
+        let now = Instant::now();
+
+        Self {
+            enabled,
+            started: now,
+            last: now,
+            stages: Vec::new(),
+        }
+    }
+
+    pub fn mark(&mut self, stage: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+
+        self.stages
+            .push((stage.to_string(), now.duration_since(self.last)));
+        self.last = now;
+    }
+
+    pub fn stages(&self) -> &[(String, Duration)] {
+        &self.stages
+    }
+
+    /// 記録した区間を表形式で標準出力へ書き出す。`--timings`未指定時は
+    /// 何も出力しない。
+    pub fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        println!("--- timings ---");
+
+        for (stage, duration) in &self.stages {
+            println!("{:<16} {:>10.3}s", stage, duration.as_secs_f64());
+        }
+
+        println!("{:<16} {:>10.3}s", "total", self.started.elapsed().as_secs_f64());
+    }
+}
+
+/// 消去後のフラッシュチップはセクタ全体が0xFFで埋まっているはずである、
+/// という前提でのブランクチェック。`sector_size`ごとに`data`を走査し、
+/// 0xFF以外のバイトを含むセクタのバイト範囲(開始位置, 長さ)を列挙する。
+/// 空の戻り値は「消去が正しく完了した」ことを意味する。
+///
+/// `WriteRom`(MBC5フラッシュカートへの書き込みと読み戻し検証)は消去
+/// コマンドの発行もブランクチェックも行わず、この関数はどのCLI
+/// サブコマンドからも呼ばれていない。
+pub fn find_non_blank_sectors(data: &[u8], sector_size: usize) -> Vec<(usize, usize)> {
+    if sector_size == 0 {
+        return Vec::new();
+    }
+
+    data.chunks(sector_size)
+        .enumerate()
+        .filter(|(_, sector)| sector.iter().any(|&b| b != 0xFF))
+        .map(|(i, sector)| (i * sector_size, sector.len()))
+        .collect()
+}
+
+/// 展開後のイメージサイズが書き込み先チップの容量に収まっているかを
+/// 確認する。`WriteRom`は非圧縮のイメージをそのまま読み込むだけで
+/// `.gz`/`.zip`の展開は行わず(`flate2`/`zip`クレートにも依存していない)、
+/// この関数はどこからも呼ばれていない。
+pub fn validate_decompressed_size(decompressed_len: usize, chip_capacity: usize) -> anyhow::Result<()> {
+    if decompressed_len > chip_capacity {
+        anyhow::bail!(
+            "decompressed image is {} bytes, which exceeds the {}-byte target chip capacity",
+            decompressed_len,
+            chip_capacity
+        );
+    }
+
+    Ok(())
+}
+
+/// `--selftest`の判定結果。アドレス/データ線の断線・半田不良が疑われる
+/// ビット位置を報告する。
+pub struct LineContinuityReport {
+    /// 全サンプルを通じて0/1のどちらか一方にしか観測されなかったデータ
+    /// ビット位置(0-7)。
+    pub stuck_data_bits: Vec<u8>,
+    /// このビットだけが異なるアドレスの組を十分な数観測したにも関わらず、
+    /// 読み出しバイトが常に一致していたアドレスビット位置。
+    pub stuck_address_bits: Vec<u8>,
+    pub samples_taken: usize,
+}
+
+impl LineContinuityReport {
+    pub fn is_clean(&self) -> bool {
+        self.stuck_data_bits.is_empty() && self.stuck_address_bits.is_empty()
+    }
+}
+
+/// `(アドレス, バイト)`のサンプル列から、アドレス/データ線の断線・半田
+/// 不良の疑いがあるビット位置を検出するヒューリスティック。実際のROM
+/// 内容に依存する統計的な判定のため、確定診断ではない
+/// -- 単調な内容(同じバイトが延々と続く領域)ばかりをサンプリングすると
+/// 見落とし得る。呼び出し側は`--selftest`のように、あくまで補助的な
+/// 早期警告として使うこと。
+pub fn analyze_line_continuity(samples: &[(u16, u8)]) -> LineContinuityReport {
+    let mut seen_0 = [false; 8];
+    let mut seen_1 = [false; 8];
+
+    for &(_, byte) in samples {
+        for bit in 0..8 {
+            if byte & (1 << bit) != 0 {
+                seen_1[bit] = true;
+            } else {
+                seen_0[bit] = true;
+            }
+        }
+    }
+
+    let stuck_data_bits = (0..8u8)
+        .filter(|&bit| !(seen_0[bit as usize] && seen_1[bit as usize]))
+        .collect();
+
+    // 十分な数(4組以上)の「このビットだけが異なるアドレスの組」が
+    // 見つかり、かつそれらが常に同じバイトを返していれば、そのアドレス
+    // ビットが立っていないかのように見える(=固着の疑いがある)と判定する。
+    const MIN_PAIRS: usize = 4;
+
+    let stuck_address_bits = (0..14u16)
+        .filter(|&bit| {
+            let mask = 1u16 << bit;
+            let mut pairs = 0;
+            let mut identical = 0;
+
+            for &(addr, byte) in samples {
+                let partner = addr ^ mask;
+
+                if let Some(&(_, partner_byte)) =
+                    samples.iter().find(|&&(a, _)| a == partner)
+                {
+                    pairs += 1;
+
+                    if byte == partner_byte {
+                        identical += 1;
+                    }
+                }
+            }
+
+            pairs >= MIN_PAIRS && identical == pairs
+        })
+        .map(|bit| bit as u8)
+        .collect();
+
+    LineContinuityReport {
+        stuck_data_bits,
+        stuck_address_bits,
+        samples_taken: samples.len(),
+    }
+}
+
+/// カートリッジがデータバスをドライブしていない(バンク有効化ミスや
+/// 破損など)場合、読み出したバイトがアドレスの下位バイトと一致する
+/// "open-bus"応答になることが多い。この一致が長く連続したら異常として
+/// フラグを立てる。
+pub struct OpenBusDetector {
+    threshold: u32,
+    run_start: Option<u32>,
+    run_len: u32,
+    reported: bool,
+}
+
+impl OpenBusDetector {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            run_start: None,
+            run_len: 0,
+            reported: false,
+        }
+    }
+
+    /// 1バイトを供給し、しきい値に到達した瞬間だけ検出範囲を返す。
+    pub fn feed(&mut self, addr: u32, byte: u8) -> Option<(u32, u32)> {
+        if byte == (addr & 0xFF) as u8 {
+            if self.run_start.is_none() {
+                self.run_start = Some(addr);
+                self.run_len = 0;
+                self.reported = false;
+            }
+
+            self.run_len += 1;
+
+            if self.run_len >= self.threshold && !self.reported {
+                self.reported = true;
+
+                return Some((self.run_start.unwrap(), addr));
+            }
+        } else {
+            self.run_start = None;
+            self.run_len = 0;
+            self.reported = false;
+        }
+
+        None
+    }
+}
+
+/// `--hash-algos`で選択できるダイジェストの種類。No-Intro等のROM
+/// カタログはこの3つ(CRC32/MD5/SHA-1)を併記するのが慣習だが、SHA-256を
+/// 使いたい利用者向けにも対応する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgo {
+    Crc32,
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                HashAlgo::Crc32 => "crc32",
+                HashAlgo::Md5 => "md5",
+                HashAlgo::Sha1 => "sha1",
+                HashAlgo::Sha256 => "sha256",
+            }
+        )
+    }
+}
+
+impl FromStr for HashAlgo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "crc32" => HashAlgo::Crc32,
+            "md5" => HashAlgo::Md5,
+            "sha1" => HashAlgo::Sha1,
+            "sha256" => HashAlgo::Sha256,
+            other => anyhow::bail!(
+                "invalid hash algorithm {:?}: expected one of crc32, md5, sha1, sha256",
+                other
+            ),
+        })
+    }
+}
+
+/// `--hash-algos crc32,sha256`のようなカンマ区切りの指定をパースした
+/// `HashAlgo`の並び。重複や大文字小文字の揺れはそのまま許容し、指定
+/// 順を保持する。ただし表示順は`StreamingHashes::finish`が返す固定順
+/// (crc32, md5, sha1, sha256)で、指定順には従わない。
+#[derive(Debug, Clone)]
+pub struct HashAlgoList(pub Vec<HashAlgo>);
+
+impl FromStr for HashAlgoList {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        s.split(',')
+            .map(|part| HashAlgo::from_str(part.trim()))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(HashAlgoList)
+    }
+}
+
+/// 複数のダイジェストを1回のストリーミング走査で計算する。呼び出す側が
+/// 指定した`HashAlgo`だけを内部に持つため、使わないアルゴリズムの計算
+/// コストを払わずに済む。
+pub struct StreamingHashes {
+    crc32: Option<crc32fast::Hasher>,
+    md5: Option<md5::Md5>,
+    sha1: Option<sha1::Sha1>,
+    sha256: Option<Sha256>,
+}
+
+impl StreamingHashes {
+    pub fn new(algos: &[HashAlgo]) -> Self {
+        Self {
+            crc32: algos.contains(&HashAlgo::Crc32).then(crc32fast::Hasher::new),
+            md5: algos.contains(&HashAlgo::Md5).then(md5::Md5::new),
+            sha1: algos.contains(&HashAlgo::Sha1).then(sha1::Sha1::new),
+            sha256: algos.contains(&HashAlgo::Sha256).then(Sha256::new),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        if let Some(hasher) = &mut self.crc32 {
+            hasher.update(data);
+        }
+
+        if let Some(hasher) = &mut self.md5 {
+            Digest::update(hasher, data);
+        }
+
+        if let Some(hasher) = &mut self.sha1 {
+            Digest::update(hasher, data);
+        }
+
+        if let Some(hasher) = &mut self.sha256 {
+            Digest::update(hasher, data);
+        }
+    }
+
+    /// 指定された(=`Some`だった)アルゴリズムだけを、`HashAlgo`の表示順で
+    /// `(アルゴリズム, 16進ダイジェスト)`として返す。
+    pub fn finish(self) -> Vec<(HashAlgo, String)> {
+        let mut out = Vec::new();
+
+        if let Some(hasher) = self.crc32 {
+            out.push((HashAlgo::Crc32, format!("{:08x}", hasher.finalize())));
+        }
+
+        if let Some(hasher) = self.md5 {
+            out.push((HashAlgo::Md5, format!("{:x}", hasher.finalize())));
+        }
+
+        if let Some(hasher) = self.sha1 {
+            out.push((HashAlgo::Sha1, format!("{:x}", hasher.finalize())));
+        }
+
+        if let Some(hasher) = self.sha256 {
+            out.push((HashAlgo::Sha256, format!("{:x}", hasher.finalize())));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_update_returns_the_sample_unchanged() {
+        let mut ewma = Ewma::new(0.5);
+
+        assert_eq!(ewma.update(10.0), 10.0);
+    }
+
+    #[test]
+    fn later_updates_blend_the_new_sample_with_the_running_value() {
+        let mut ewma = Ewma::new(0.5);
+
+        ewma.update(10.0);
+        let value = ewma.update(20.0);
+
+        assert_eq!(value, 15.0);
+    }
+
+    #[test]
+    fn a_smoothing_of_one_tracks_the_latest_sample_exactly() {
+        let mut ewma = Ewma::new(1.0);
+
+        ewma.update(10.0);
+        assert_eq!(ewma.update(20.0), 20.0);
+        assert_eq!(ewma.update(5.0), 5.0);
+    }
+
+    #[test]
+    fn a_smoothing_of_zero_ignores_new_samples_after_the_first() {
+        let mut ewma = Ewma::new(0.0);
+
+        ewma.update(10.0);
+        assert_eq!(ewma.update(999.0), 10.0);
+    }
+}